@@ -1,5 +1,5 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 use crate::search::{Config as SearchConfig, scan_dir};
@@ -56,7 +56,7 @@ fn which(cmd: &str) -> bool {
 // ---------------------------------------------------------------------------
 
 fn calc_stats(mut samples: Vec<f64>) -> (f64, f64, f64, f64) {
-    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    samples.sort_by(f64::total_cmp);
     let n = samples.len() as f64;
     let avg = samples.iter().sum::<f64>() / n;
     let median = if samples.len().is_multiple_of(2) {
@@ -87,10 +87,9 @@ fn clear_progress(live: bool) {
 // Run ldx — uses internal scan_dir directly
 // ---------------------------------------------------------------------------
 
-fn bench_ldx(dir: &PathBuf, config: &BenchConfig) -> Option<BenchResult> {
+fn bench_ldx(dir: &Path, config: &BenchConfig) -> Option<BenchResult> {
     let search_config = SearchConfig {
         case_sensitive: false,
-        quiet: true,
         all: true,
         dirs_only: false,
         extension: None,
@@ -100,6 +99,8 @@ fn bench_ldx(dir: &PathBuf, config: &BenchConfig) -> Option<BenchResult> {
         collect_paths: false,
         collect_errors: false,
         exclude: vec![],
+        cancel: None,
+        deadline: None,
     };
 
     let mut speeds = Vec::new();
@@ -111,7 +112,7 @@ fn bench_ldx(dir: &PathBuf, config: &BenchConfig) -> Option<BenchResult> {
             &format!("ldx  {} [{}/{}]", dir.display(), i + 1, config.runs),
         );
 
-        let result = scan_dir(dir, &search_config);
+        let result = scan_dir(dir, &search_config).ok()?;
         let e = result.files + result.dirs;
         let secs = result.duration.as_secs_f64();
 
@@ -144,7 +145,7 @@ fn bench_ldx(dir: &PathBuf, config: &BenchConfig) -> Option<BenchResult> {
 
     Some(BenchResult {
         tool: "ldx".to_string(),
-        dir: dir.clone(),
+        dir: dir.to_path_buf(),
         avg,
         median,
         min,
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::exit;
+
+// ---------------------------------------------------------------------------
+// Search budget — Ctrl-C sets the flag the walker polls, and the walker sets
+// it itself once the config's --timeout deadline passes, so the summary and
+// stats still print for whatever was scanned. While results print, Ctrl-C
+// cuts the listing short between lines instead of killing the process.
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    TimedOut,
+    Interrupted,
+}
+
+#[derive(Clone)]
+pub struct Budget {
+    flag: Arc<AtomicBool>,
+    interrupted: Arc<AtomicBool>,
+    /// Ctrl-C only cancels while a walk runs or its results print;
+    /// otherwise it exits.
+    latched: Arc<AtomicBool>,
+}
+
+impl Budget {
+    /// Installs the Ctrl-C handler. Call once, before the first search.
+    pub fn install() -> Self {
+        let budget = Self::new();
+        let handler = budget.clone();
+        // without a handler Ctrl-C still works; it just kills the process
+        let _ = ctrlc::set_handler(move || {
+            if !handler.latched.load(Ordering::SeqCst)
+                || handler.interrupted.swap(true, Ordering::SeqCst)
+            {
+                // a second Ctrl-C, or one outside the walk and listing, is
+                // not negotiable
+                std::process::exit(i32::from(exit::INTERRUPTED));
+            }
+            handler.flag.store(true, Ordering::SeqCst);
+        });
+        budget
+    }
+
+    fn new() -> Self {
+        Self {
+            flag: Arc::new(AtomicBool::new(false)),
+            interrupted: Arc::new(AtomicBool::new(false)),
+            latched: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// The flag to hand to the search config.
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.flag)
+    }
+
+    /// Marks a walk, or the printing of its results, as running, so Ctrl-C
+    /// stops it instead of exiting.
+    pub fn start(&self) {
+        self.latched.store(true, Ordering::SeqCst);
+    }
+
+    /// Marks the walk or listing as over and says why the search ended
+    /// early, if it did.
+    pub fn finish(&self) -> Option<Stop> {
+        self.latched.store(false, Ordering::SeqCst);
+        self.stopped()
+    }
+
+    /// Whether Ctrl-C was pressed. Listings check it between lines.
+    pub fn interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    /// Only Ctrl-C and the deadline set the flag, so a set flag without
+    /// Ctrl-C means the time ran out.
+    pub fn stopped(&self) -> Option<Stop> {
+        if self.interrupted() {
+            Some(Stop::Interrupted)
+        } else if self.flag.load(Ordering::SeqCst) {
+            Some(Stop::TimedOut)
+        } else {
+            None
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_flag_set_by_the_walker_reads_as_a_timeout() {
+        let budget = Budget::new();
+        budget.start();
+        assert_eq!(budget.stopped(), None);
+        budget.flag().store(true, Ordering::SeqCst);
+        assert_eq!(budget.finish(), Some(Stop::TimedOut));
+        budget.interrupted.store(true, Ordering::SeqCst);
+        assert_eq!(budget.stopped(), Some(Stop::Interrupted));
+    }
+}
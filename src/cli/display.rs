@@ -1,5 +1,12 @@
+use crate::cancel::Budget;
+use crate::cli::theme::{
+    accent, highlight, hyperlink, label, name_style, paint_parent, paint_styled, success, warning,
+};
 use crate::config::{FlagDef, LdxConfig, is_flag_available};
+use crate::search::SpanMatcher;
 use colored::Colorize;
+use parex::EntryKind;
+use std::path::{Component, Path, PathBuf};
 
 // ---------------------------------------------------------------------------
 // Number formatting
@@ -17,6 +24,25 @@ pub fn fmt_num(n: usize) -> String {
     result.chars().rev().collect()
 }
 
+// ---------------------------------------------------------------------------
+// Size formatting
+// ---------------------------------------------------------------------------
+
+pub fn fmt_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Help output
 // ---------------------------------------------------------------------------
@@ -25,7 +51,7 @@ pub fn print_help(config: &LdxConfig) {
     println!();
     println!(
         "  {}  {}",
-        "ldx".bright_cyan().bold(),
+        accent("ldx").bold(),
         "blazing-fast parallel file search".dimmed()
     );
     println!(
@@ -40,7 +66,7 @@ pub fn print_help(config: &LdxConfig) {
     println!(
         "  {} {} {}",
         "Usage:".bold(),
-        "ldx".bright_cyan(),
+        accent("ldx"),
         "[pattern] [options]".dimmed()
     );
     println!();
@@ -62,12 +88,59 @@ pub fn print_help(config: &LdxConfig) {
     for flag in &flags {
         println!(
             "    {}  {:<28} {}",
-            format!("-{}", flag.short).bright_cyan(),
-            format!("--{}", flag.long).cyan(),
+            accent(&format!("-{}", flag.short)),
+            label(&format!("--{}", flag.long)),
             flag.description.dimmed()
         );
     }
 
+    let options: &[(&str, &str)] = &[
+        ("--absolute", "Print absolute paths"),
+        ("--by", "Rank --top by size|mtime"),
+        ("--bytes", "With --group-by, add byte totals per group"),
+        ("--color", "Colorize output: auto|always|never"),
+        ("--copy-to DIR", "Copy matches into DIR"),
+        ("--delete", "Delete matches after a preview and prompt"),
+        ("--dry-run", "Preview --delete/--copy-to/--move-to"),
+        ("--exclude", "Skip directories (comma-separated)"),
+        ("-x, --exec CMD", "Run CMD per match ({} {/} {//} {.} {/.})"),
+        ("-X, --exec-batch CMD", "Run CMD once with every match"),
+        ("--flatten", "With --copy-to/--move-to, drop subdirectories"),
+        ("--group-by", "Count matches per ext|dir|depth"),
+        ("--hyperlink", "Clickable file:// links: auto|always|never"),
+        ("-I, --index", "Answer from the on-disk index (ldx index)"),
+        ("-j, --jobs N", "Parallel -x commands (default: threads)"),
+        ("--json", "Print matches and skipped paths as JSON"),
+        ("--live", "Always scan; skip the daemon and the index"),
+        ("--move-to DIR", "Move matches into DIR"),
+        ("--on-conflict", "Existing target: skip|overwrite|rename"),
+        (
+            "--open-with CMD",
+            "Open with CMD ({} per file, {+} all at once)",
+        ),
+        ("--pick", "Choose matches in an interactive picker"),
+        ("--relative-to", "Print paths relative to DIR"),
+        ("--reveal", "Show matches in the file manager"),
+        ("--reverse", "Reverse the output order"),
+        ("--sort", "Sort by name|path|size|mtime|depth[-desc]"),
+        ("--timeout", "Stop the walk after e.g. 500ms, 5s, 2m"),
+        ("--top", "Show only the N largest/newest matches"),
+        ("--trash", "With --delete, move to the XDG trash instead"),
+        ("--tree", "Group matches under their parent directories"),
+        ("--watch", "Keep watching; print + / - as matches change"),
+        ("--yes", "With --delete, skip the confirmation prompt"),
+    ];
+    println!();
+    println!("  {}", "Options:".bold());
+    for (flag, desc) in options {
+        println!(
+            "    {}  {:<28} {}",
+            "  ".dimmed(),
+            label(flag),
+            desc.dimmed()
+        );
+    }
+
     let mgmt: &[(&str, &str)] = &[
         ("--check", "Validate config"),
         ("--config", "Show config path"),
@@ -82,7 +155,7 @@ pub fn print_help(config: &LdxConfig) {
         println!(
             "    {}  {:<28} {}",
             "  ".dimmed(),
-            flag.cyan(),
+            label(flag),
             desc.dimmed()
         );
     }
@@ -95,7 +168,7 @@ pub fn print_help(config: &LdxConfig) {
         for (name, expansion) in &aliases {
             println!(
                 "    {:<16} {}  {}",
-                name.bright_cyan(),
+                accent(name),
                 "→".dimmed(),
                 expansion.dimmed()
             );
@@ -110,8 +183,8 @@ pub fn print_help(config: &LdxConfig) {
         for (_, flag) in &custom {
             println!(
                 "    {}  {:<28} {}",
-                format!("-{}", flag.short).bright_cyan(),
-                format!("--{}", flag.long).cyan(),
+                accent(&format!("-{}", flag.short)),
+                label(&format!("--{}", flag.long)),
                 flag.description.dimmed()
             );
         }
@@ -120,6 +193,202 @@ pub fn print_help(config: &LdxConfig) {
     println!();
 }
 
+// ---------------------------------------------------------------------------
+// Result path text — relative to the root, absolute, or --relative-to DIR
+// ---------------------------------------------------------------------------
+
+pub fn display_path(path: &Path, root: &Path, f: &crate::cli::flags::ParsedFlags) -> String {
+    let rel = if f.absolute {
+        path.to_path_buf()
+    } else if let Some(base) = &f.relative_to {
+        relative_path(path, base)
+    } else {
+        path.strip_prefix(root).unwrap_or(path).to_path_buf()
+    };
+    if rel.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        rel.to_string_lossy().into_owned()
+    }
+}
+
+/// Lexical `path` relative to `base`; both are expected to be absolute.
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let shared = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    if shared == 0 {
+        // different drive on Windows — nothing to be relative to
+        return path.iter().collect();
+    }
+    let mut rel = PathBuf::new();
+    for _ in shared..base.len() {
+        rel.push("..");
+    }
+    rel.extend(&path[shared..]);
+    rel
+}
+
+// ---------------------------------------------------------------------------
+// Print matched paths
+// ---------------------------------------------------------------------------
+
+/// Stops early, between lines, once `budget` sees Ctrl-C.
+pub fn print_matches(
+    result: &crate::search::ScanResult,
+    root: &Path,
+    f: &crate::cli::flags::ParsedFlags,
+    matcher: &dyn SpanMatcher,
+    budget: &Budget,
+) {
+    if f.quiet || f.all {
+        return;
+    }
+    if f.tree {
+        let label = display_path(root, root, f);
+        crate::cli::tree::print_tree(&result.paths, root, &label, matcher, budget);
+        return;
+    }
+    for path in &result.paths {
+        if budget.interrupted() {
+            return;
+        }
+        let disp = display_path(path, root, f);
+        println!(
+            "{}",
+            hyperlink(&paint_match(&disp, path, None, matcher), path)
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Highlight the matched span of the file name within a displayed path
+// ---------------------------------------------------------------------------
+
+/// `kind` is the entry's kind when the caller has it; otherwise the
+/// matcher's, if it only matches one kind.
+pub fn paint_match(
+    disp: &str,
+    path: &Path,
+    kind: Option<&EntryKind>,
+    matcher: &dyn SpanMatcher,
+) -> String {
+    let name = Path::new(disp)
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    let name_start = disp.len() - name.len();
+    let spans = matcher.find(&name).unwrap_or_default();
+    let only = matcher.kind();
+    let style = name_style(path, kind.or(only.as_ref()));
+
+    let mut out = paint_parent(&disp[..name_start]);
+    let mut cursor = name_start;
+    for span in spans {
+        let (start, end) = (name_start + span.start, name_start + span.end);
+        if start < cursor || end > disp.len() || start == end {
+            continue;
+        }
+        out.push_str(&paint_styled(&disp[cursor..start], style));
+        out.push_str(&highlight(&disp[start..end]).to_string());
+        cursor = end;
+    }
+    out.push_str(&paint_styled(&disp[cursor..], style));
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Print --top winners with their size or mtime
+// ---------------------------------------------------------------------------
+
+pub fn print_top(
+    result: &crate::top::TopResult,
+    root: &Path,
+    f: &crate::cli::flags::ParsedFlags,
+    matcher: &dyn SpanMatcher,
+    budget: &Budget,
+) {
+    if f.quiet {
+        return;
+    }
+    for entry in &result.entries {
+        if budget.interrupted() {
+            return;
+        }
+        let disp = display_path(&entry.path, root, f);
+        let value = match f.rank_by.unwrap_or_default() {
+            crate::top::RankKey::Size => fmt_size(entry.size),
+            crate::top::RankKey::Mtime => chrono::DateTime::<chrono::Local>::from(entry.modified)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+        };
+        println!(
+            "{:>16}  {}",
+            value.dimmed(),
+            hyperlink(
+                &paint_match(&disp, &entry.path, Some(&entry.kind), matcher),
+                &entry.path
+            )
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Print --group-by histogram
+// ---------------------------------------------------------------------------
+
+pub fn print_groups(
+    result: &crate::group::GroupResult,
+    f: &crate::cli::flags::ParsedFlags,
+    budget: &Budget,
+) {
+    if f.quiet {
+        return;
+    }
+    let sizes = f.bytes;
+    let peak = result
+        .groups
+        .first()
+        .map(|g| {
+            if sizes {
+                g.bytes as f64
+            } else {
+                g.count as f64
+            }
+        })
+        .unwrap_or(0.0);
+    let width = result.groups.iter().map(|g| g.key.len()).max().unwrap_or(0);
+
+    for g in &result.groups {
+        if budget.interrupted() {
+            return;
+        }
+        let value = if sizes {
+            g.bytes as f64
+        } else {
+            g.count as f64
+        };
+        let bar_len = if peak > 0.0 {
+            ((value / peak) * 24.0).ceil() as usize
+        } else {
+            0
+        };
+        let size_col = if sizes {
+            format!("  {:>10}", fmt_size(g.bytes))
+        } else {
+            String::new()
+        };
+        println!(
+            "  {:<width$}  {:>10}{}  {}",
+            accent(&g.key),
+            fmt_num(g.count),
+            size_col,
+            "█".repeat(bar_len).dimmed(),
+            width = width
+        );
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Print scan result summary line
 // ---------------------------------------------------------------------------
@@ -157,6 +426,83 @@ pub fn print_result(
     }
 }
 
+// ---------------------------------------------------------------------------
+// Print the combined summary line for several roots
+// ---------------------------------------------------------------------------
+
+pub fn print_total(
+    total: &crate::search::ScanResult,
+    roots: usize,
+    noun: &str,
+    f: &crate::cli::flags::ParsedFlags,
+) {
+    let n = total.matches;
+    let what = if f.all {
+        if n == 1 { "file" } else { "files" }
+    } else if f.dirs_only {
+        if n == 1 {
+            "matching directory"
+        } else {
+            "matching directories"
+        }
+    } else if n == 1 {
+        "matching file"
+    } else {
+        "matching files"
+    };
+    println!(
+        "Total: {} {} across {} {}{} in {:.3}s",
+        fmt_num(n),
+        what,
+        roots,
+        noun,
+        if roots == 1 { "" } else { "s" },
+        total.duration.as_secs_f64()
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Print why a search ended early
+// ---------------------------------------------------------------------------
+
+pub fn print_stop(stop: Option<crate::cancel::Stop>, indent: &str) {
+    let marker = match stop {
+        None => return,
+        Some(crate::cancel::Stop::TimedOut) => "(timed out)",
+        Some(crate::cancel::Stop::Interrupted) => "(interrupted)",
+    };
+    println!(
+        "{}{} results cover only what was scanned",
+        indent,
+        warning(marker)
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Print -x/--exec outcome
+// ---------------------------------------------------------------------------
+
+pub fn print_exec(summary: &crate::exec::ExecSummary) {
+    if summary.ran == 0 {
+        return;
+    }
+    let ran = format!(
+        "Ran {} command{}",
+        fmt_num(summary.ran),
+        if summary.ran == 1 { "" } else { "s" }
+    );
+    if summary.failed == 0 {
+        println!("{}: {}", ran, success("all succeeded"));
+    } else {
+        println!(
+            "{}: {} (highest exit code {})",
+            ran,
+            warning(&format!("{} failed", fmt_num(summary.failed))),
+            summary.code
+        );
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Print stats line
 // ---------------------------------------------------------------------------
@@ -191,3 +537,28 @@ pub fn print_stats(
         );
     }
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_path_walks_up_to_common_ancestor() {
+        assert_eq!(
+            relative_path(Path::new("/home/u/src/a.rs"), Path::new("/home/u/docs")),
+            PathBuf::from("../src/a.rs")
+        );
+        assert_eq!(
+            relative_path(Path::new("/home/u/src/a.rs"), Path::new("/home/u")),
+            PathBuf::from("src/a.rs")
+        );
+        assert_eq!(
+            relative_path(Path::new("/home/u"), Path::new("/home/u")),
+            PathBuf::new()
+        );
+    }
+}
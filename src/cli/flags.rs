@@ -1,7 +1,14 @@
 use anyhow::{Result, bail};
 use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::cli::theme::When;
 use crate::config::LdxConfig;
+use crate::exec::{CommandTemplate, ExecMode};
+use crate::group::GroupKey;
+use crate::ops::Conflict;
+use crate::sort::{SortKey, SortSpec};
+use crate::top::RankKey;
 
 // ---------------------------------------------------------------------------
 // Parsed flags
@@ -10,7 +17,8 @@ use crate::config::LdxConfig;
 #[derive(Default)]
 pub struct ParsedFlags {
     pub pattern: Option<String>,
-    pub dir: PathBuf,
+    /// Search roots in command-line order; one per `-d`, `.` without any.
+    pub dirs: Vec<PathBuf>,
     pub extension: Option<String>,
     pub threads: usize,
     pub quiet: bool,
@@ -24,6 +32,39 @@ pub struct ParsedFlags {
     pub case_sensitive: bool,
     pub limit: Option<usize>,
     pub exclude: Vec<String>,
+    pub sort: Option<SortSpec>,
+    pub reverse: bool,
+    pub top: Option<usize>,
+    pub rank_by: Option<RankKey>,
+    /// Byte totals per `--group-by` bucket.
+    pub bytes: bool,
+    pub tree: bool,
+    pub group_by: Option<GroupKey>,
+    pub color: When,
+    pub hyperlink: When,
+    pub absolute: bool,
+    pub relative_to: Option<PathBuf>,
+    pub exec: Option<CommandTemplate>,
+    pub jobs: usize,
+    pub delete: bool,
+    pub dry_run: bool,
+    pub yes: bool,
+    pub trash: bool,
+    pub copy_to: Option<PathBuf>,
+    pub move_to: Option<PathBuf>,
+    pub flatten: bool,
+    pub on_conflict: Conflict,
+    pub pick: bool,
+    pub open_with: Option<String>,
+    pub reveal: bool,
+    pub cd_file: Option<PathBuf>,
+    pub index: bool,
+    /// Skip the daemon and the index; implied by -x, --delete, --copy-to and
+    /// --move-to.
+    pub live: bool,
+    pub watch: bool,
+    pub json: bool,
+    pub timeout: Option<Duration>,
     pub show_help: bool,
     pub show_version: bool,
     pub show_config: bool,
@@ -163,7 +204,7 @@ fn parse_management(raw: &[String], config: &LdxConfig) -> Option<ParsedFlags> {
             check_config,
             sync_config,
             reset_config,
-            dir: ".".into(),
+            dirs: vec![".".into()],
             threads: max_threads,
             ..Default::default()
         });
@@ -178,10 +219,22 @@ fn parse_management(raw: &[String], config: &LdxConfig) -> Option<ParsedFlags> {
 
 struct ValueFlags {
     extension: Option<String>,
-    dir: PathBuf,
+    dirs: Vec<PathBuf>,
     threads: usize,
     limit: Option<usize>,
     exclude: Vec<String>,
+    sort: Option<String>,
+    top: Option<String>,
+    by: Option<String>,
+    group_by: Option<String>,
+    relative_to: Option<PathBuf>,
+    jobs: Option<String>,
+    copy_to: Option<PathBuf>,
+    move_to: Option<PathBuf>,
+    on_conflict: Option<String>,
+    open_with: Option<String>,
+    cd_file: Option<PathBuf>,
+    timeout: Option<String>,
 }
 
 fn parse_value_flags(raw: &[String], config: &LdxConfig) -> ValueFlags {
@@ -200,12 +253,14 @@ fn parse_value_flags(raw: &[String], config: &LdxConfig) -> ValueFlags {
         .and_then(|i| raw.get(i + 1))
         .map(|s| s.trim_start_matches('.').to_lowercase());
 
-    let dir = raw
-        .iter()
-        .position(|a| flag_matches(a, &dir_s, &dir_l))
-        .and_then(|i| raw.get(i + 1))
-        .map(PathBuf::from)
-        .unwrap_or_else(|| ".".into());
+    let mut dirs: Vec<PathBuf> = raw
+        .windows(2)
+        .filter(|w| flag_matches(&w[0], &dir_s, &dir_l))
+        .map(|w| PathBuf::from(&w[1]))
+        .collect();
+    if dirs.is_empty() {
+        dirs.push(".".into());
+    }
 
     let threads = raw.iter().position(|a| flag_matches(a, &threads_s, &threads_l))
         .and_then(|i| raw.get(i + 1))
@@ -233,12 +288,96 @@ fn parse_value_flags(raw: &[String], config: &LdxConfig) -> ValueFlags {
         .map(|s| s.split(',').map(|p| p.trim().to_string()).collect())
         .unwrap_or_default();
 
+    let sort = raw
+        .iter()
+        .position(|a| a == "--sort")
+        .and_then(|i| raw.get(i + 1))
+        .cloned();
+
+    let top = raw
+        .iter()
+        .position(|a| a == "--top")
+        .and_then(|i| raw.get(i + 1))
+        .cloned();
+
+    let by = raw
+        .iter()
+        .position(|a| a == "--by")
+        .and_then(|i| raw.get(i + 1))
+        .cloned();
+
+    let group_by = raw
+        .iter()
+        .position(|a| a == "--group-by")
+        .and_then(|i| raw.get(i + 1))
+        .cloned();
+
+    let relative_to = raw
+        .iter()
+        .position(|a| a == "--relative-to")
+        .and_then(|i| raw.get(i + 1))
+        .map(PathBuf::from);
+
+    let jobs = raw
+        .iter()
+        .position(|a| a == "--jobs" || a == "-j")
+        .and_then(|i| raw.get(i + 1))
+        .cloned();
+
+    let copy_to = raw
+        .iter()
+        .position(|a| a == "--copy-to")
+        .and_then(|i| raw.get(i + 1))
+        .map(PathBuf::from);
+
+    let move_to = raw
+        .iter()
+        .position(|a| a == "--move-to")
+        .and_then(|i| raw.get(i + 1))
+        .map(PathBuf::from);
+
+    let on_conflict = raw
+        .iter()
+        .position(|a| a == "--on-conflict")
+        .and_then(|i| raw.get(i + 1))
+        .cloned();
+
+    let open_with = raw
+        .iter()
+        .position(|a| a == "--open-with")
+        .and_then(|i| raw.get(i + 1))
+        .cloned();
+
+    let cd_file = raw
+        .iter()
+        .position(|a| a == "--cd-file")
+        .and_then(|i| raw.get(i + 1))
+        .map(PathBuf::from);
+
+    let timeout = raw
+        .iter()
+        .position(|a| a == "--timeout")
+        .and_then(|i| raw.get(i + 1))
+        .cloned();
+
     ValueFlags {
         extension,
-        dir,
+        dirs,
         threads,
         limit,
         exclude,
+        sort,
+        top,
+        by,
+        group_by,
+        relative_to,
+        jobs,
+        copy_to,
+        move_to,
+        on_conflict,
+        open_with,
+        cd_file,
+        timeout,
     }
 }
 
@@ -258,6 +397,21 @@ struct BoolFlags {
     case_sensitive: bool,
     all_drives: bool,
     warn: bool,
+    reverse: bool,
+    tree: bool,
+    bytes: bool,
+    absolute: bool,
+    delete: bool,
+    dry_run: bool,
+    yes: bool,
+    trash: bool,
+    flatten: bool,
+    pick: bool,
+    reveal: bool,
+    index: bool,
+    live: bool,
+    watch: bool,
+    json: bool,
 }
 
 fn parse_bool_flags(raw: &[String], config: &LdxConfig) -> BoolFlags {
@@ -292,6 +446,21 @@ fn parse_bool_flags(raw: &[String], config: &LdxConfig) -> BoolFlags {
         case_sensitive: raw.iter().any(|a| flag_matches(a, &cs_s, &cs_l)),
         all_drives,
         warn: raw.iter().any(|a| a == "--warn" || a == "-W"),
+        reverse: raw.iter().any(|a| a == "--reverse"),
+        tree: raw.iter().any(|a| a == "--tree"),
+        bytes: raw.iter().any(|a| a == "--bytes"),
+        absolute: raw.iter().any(|a| a == "--absolute"),
+        delete: raw.iter().any(|a| a == "--delete"),
+        dry_run: raw.iter().any(|a| a == "--dry-run"),
+        yes: raw.iter().any(|a| a == "--yes"),
+        trash: raw.iter().any(|a| a == "--trash"),
+        flatten: raw.iter().any(|a| a == "--flatten"),
+        pick: raw.iter().any(|a| a == "--pick"),
+        reveal: raw.iter().any(|a| a == "--reveal"),
+        index: raw.iter().any(|a| a == "--index" || a == "-I"),
+        live: raw.iter().any(|a| a == "--live"),
+        watch: raw.iter().any(|a| a == "--watch"),
+        json: raw.iter().any(|a| a == "--json"),
     }
 }
 
@@ -315,6 +484,21 @@ fn parse_pattern(raw: &[String], config: &LdxConfig) -> Result<Option<String>> {
             limit_s,
             limit_l,
             "--exclude".into(),
+            "--sort".into(),
+            "--top".into(),
+            "--by".into(),
+            "--group-by".into(),
+            "--color".into(),
+            "--hyperlink".into(),
+            "--relative-to".into(),
+            "--jobs".into(),
+            "-j".into(),
+            "--copy-to".into(),
+            "--move-to".into(),
+            "--on-conflict".into(),
+            "--open-with".into(),
+            "--cd-file".into(),
+            "--timeout".into(),
         ]
     };
 
@@ -334,6 +518,37 @@ fn parse_pattern(raw: &[String], config: &LdxConfig) -> Result<Option<String>> {
                 "--reset",
                 "--exclude",
                 "--warn",
+                "--sort",
+                "--reverse",
+                "--top",
+                "--by",
+                "--tree",
+                "--group-by",
+                "--bytes",
+                "--color",
+                "--hyperlink",
+                "--absolute",
+                "--relative-to",
+                "--jobs",
+                "-j",
+                "--delete",
+                "--dry-run",
+                "--yes",
+                "--trash",
+                "--copy-to",
+                "--move-to",
+                "--flatten",
+                "--on-conflict",
+                "--pick",
+                "--open-with",
+                "--reveal",
+                "--cd-file",
+                "--index",
+                "-I",
+                "--live",
+                "--watch",
+                "--json",
+                "--timeout",
             ]
             .iter()
             .map(|s| s.to_string()),
@@ -403,23 +618,126 @@ pub fn validate_combos(
     Ok(())
 }
 
+// ---------------------------------------------------------------------------
+// -x/--exec and -X/--exec-batch — the command runs to `;` or end of args
+// ---------------------------------------------------------------------------
+
+fn split_exec(raw: Vec<String>) -> Result<(Vec<String>, Option<CommandTemplate>)> {
+    let is_exec = |a: &str| matches!(a, "-x" | "--exec" | "-X" | "--exec-batch");
+    let Some(start) = raw.iter().position(|a| is_exec(a)) else {
+        return Ok((raw, None));
+    };
+    let mode = if matches!(raw[start].as_str(), "-X" | "--exec-batch") {
+        ExecMode::Batch
+    } else {
+        ExecMode::Each
+    };
+    let end = raw[start + 1..]
+        .iter()
+        .position(|a| a == ";")
+        .map_or(raw.len(), |i| start + 1 + i);
+
+    let command = raw[start + 1..end].to_vec();
+    if command.is_empty() {
+        bail!(
+            "{} needs a command to run, e.g. -x chmod +x {{}}",
+            raw[start]
+        );
+    }
+    let rest: Vec<String> = raw[..start]
+        .iter()
+        .chain(raw.get(end + 1..).unwrap_or_default())
+        .cloned()
+        .collect();
+    if rest.iter().any(|a| is_exec(a)) {
+        bail!("-x/--exec and -X/--exec-batch can only be given once.");
+    }
+    Ok((rest, Some(CommandTemplate::new(mode, command))))
+}
+
+// ---------------------------------------------------------------------------
+// auto|always|never switches (--color, --hyperlink)
+// ---------------------------------------------------------------------------
+
+fn parse_when(raw: &[String], flag: &str) -> Result<When> {
+    match raw
+        .iter()
+        .position(|a| a == flag)
+        .and_then(|i| raw.get(i + 1))
+    {
+        None => Ok(When::Auto),
+        Some(v) => v.parse().map_err(|_| {
+            anyhow::anyhow!(
+                "Invalid {} value: {:?}. Expected auto, always or never.",
+                flag,
+                v
+            )
+        }),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// --timeout durations: 500ms, 5s, 2m; a bare number is seconds
+// ---------------------------------------------------------------------------
+
+fn parse_duration(v: &str) -> Result<Duration> {
+    let split = v
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(v.len());
+    let (num, unit) = v.split_at(split);
+    let scale = match unit {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => bail!(
+            "--timeout expects a duration like 500ms, 5s or 2m, got {:?}.",
+            v
+        ),
+    };
+    // too large for a Duration is as unusable as not a number
+    match num
+        .parse::<f64>()
+        .map(|n| Duration::try_from_secs_f64(n * scale))
+    {
+        Ok(Ok(limit)) if !limit.is_zero() => Ok(limit),
+        _ => bail!(
+            "--timeout expects a duration like 500ms, 5s or 2m, got {:?}.",
+            v
+        ),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // parse_args — orchestration
 // ---------------------------------------------------------------------------
 
 pub fn parse_args(config: &LdxConfig) -> Result<ParsedFlags> {
     let raw: Vec<String> = std::env::args().skip(1).collect();
+    // split the command off first so its words are never alias-expanded
+    let (raw, exec) = split_exec(raw)?;
     let raw = expand_aliases(raw, config);
     let raw = resolve_custom(raw, config);
 
-    if let Some(flags) = parse_management(&raw, config) {
+    let color = parse_when(&raw, "--color")?;
+    let hyperlink = parse_when(&raw, "--hyperlink")?;
+
+    if let Some(mut flags) = parse_management(&raw, config) {
+        flags.color = color;
+        flags.hyperlink = hyperlink;
         return Ok(flags);
     }
 
     let v = parse_value_flags(&raw, config);
-    let b = parse_bool_flags(&raw, config);
+    let mut b = parse_bool_flags(&raw, config);
     let pattern = parse_pattern(&raw, config)?;
 
+    if b.reveal && v.open_with.is_some() {
+        bail!("--reveal and --open-with cannot be used together.");
+    }
+    // both are ways of opening, so they share -o's rules
+    b.open |= b.reveal || v.open_with.is_some();
+
     validate_combos(
         &pattern,
         &v.extension,
@@ -430,18 +748,217 @@ pub fn parse_args(config: &LdxConfig) -> Result<ParsedFlags> {
         b.dirs_only,
     )?;
 
-    let limit = if b.first || b.where_mode {
+    // under the shell wrapper -w offers every match in the picker
+    let limit = if b.first || (b.where_mode && v.cd_file.is_none()) {
         Some(1)
     } else {
         v.limit
     };
 
+    if v.sort.is_none() && raw.iter().any(|a| a == "--sort") {
+        bail!("--sort needs a key: name, path, size, mtime or depth (optionally with -asc/-desc).");
+    }
+    // the limit stops the walk, so sorting afterwards would only order whatever came first
+    if (b.first || v.limit.is_some()) && (v.sort.is_some() || b.reverse) {
+        bail!("--sort/--reverse cannot be combined with -1/--first or -L/--limit.");
+    }
+
+    // --reverse on its own still needs a stable order to flip
+    let sort = match v.sort.as_deref().map(str::parse::<SortSpec>).transpose()? {
+        None if b.reverse => Some(SortSpec {
+            key: SortKey::Path,
+            descending: false,
+        }),
+        sort => sort,
+    };
+
+    let top = match v.top.as_deref() {
+        None => None,
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) if n > 0 => Some(n),
+            _ => bail!("--top expects a positive number, got {:?}.", n),
+        },
+    };
+    if top.is_some() && sort.is_some() {
+        bail!("--top cannot be combined with --sort/--reverse; results are already ranked.");
+    }
+    let group_by = v
+        .group_by
+        .as_deref()
+        .map(str::parse::<GroupKey>)
+        .transpose()?;
+    if group_by.is_some() && (top.is_some() || sort.is_some() || b.tree) {
+        bail!("--group-by cannot be combined with --top, --sort/--reverse or --tree.");
+    }
+
+    let rank_by = v.by.as_deref().map(str::parse::<RankKey>).transpose()?;
+    if rank_by.is_some() && top.is_none() {
+        bail!("--by only applies to --top.");
+    }
+    if b.bytes && group_by.is_none() {
+        bail!("--bytes only applies to --group-by.");
+    }
+    // both reports need the whole tree; a limit would silently skew them
+    if (top.is_some() || group_by.is_some()) && (b.first || v.limit.is_some()) {
+        bail!("--top and --group-by cannot be combined with -1/--first or -L/--limit.");
+    }
+    if b.tree && (b.all || top.is_some()) {
+        bail!("--tree needs matched paths; it cannot be combined with -a/--all-files or --top.");
+    }
+
+    let jobs = match v.jobs.as_deref() {
+        None => v.threads,
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => bail!("--jobs expects a positive number, got {:?}.", n),
+        },
+    };
+    if v.jobs.is_some() && exec.as_ref().map(|e| e.mode) != Some(ExecMode::Each) {
+        bail!("-j/--jobs only applies to -x/--exec.");
+    }
+    if exec.is_some()
+        && (b.all || b.open || b.where_mode || b.tree || top.is_some() || group_by.is_some())
+    {
+        bail!(
+            "-x/--exec cannot be combined with -a/--all-files, -o/--open, -w/--where, --tree, --top or --group-by."
+        );
+    }
+
+    let relocating = v.copy_to.is_some() || v.move_to.is_some();
+    let on_conflict = v
+        .on_conflict
+        .as_deref()
+        .map(str::parse::<Conflict>)
+        .transpose()?
+        .unwrap_or_default();
+    if (b.yes || b.trash) && !b.delete {
+        bail!("--yes and --trash only apply to --delete.");
+    }
+    if b.dry_run && !b.delete && !relocating {
+        bail!("--dry-run only applies to --delete, --copy-to or --move-to.");
+    }
+    if (b.flatten || v.on_conflict.is_some()) && !relocating {
+        bail!("--flatten and --on-conflict only apply to --copy-to or --move-to.");
+    }
+    if [b.delete, v.copy_to.is_some(), v.move_to.is_some()]
+        .iter()
+        .filter(|&&x| x)
+        .count()
+        > 1
+    {
+        bail!("--delete, --copy-to and --move-to cannot be used together.");
+    }
+    if (b.delete || relocating)
+        && (b.all
+            || b.open
+            || b.where_mode
+            || b.tree
+            || top.is_some()
+            || group_by.is_some()
+            || exec.is_some())
+    {
+        bail!(
+            "--delete, --copy-to and --move-to cannot be combined with -a/--all-files, -o/--open, -w/--where, --tree, --top, --group-by or -x/--exec."
+        );
+    }
+    // macOS and Windows keep their trash elsewhere; files moved to an XDG
+    // trash there would never show up in Finder or the Recycle Bin
+    #[cfg(any(windows, target_os = "macos"))]
+    if b.trash {
+        bail!("--trash needs an XDG desktop trash and is not supported on macOS or Windows.");
+    }
+
+    if b.pick && (b.all || top.is_some() || group_by.is_some()) {
+        bail!(
+            "--pick needs matched paths; it cannot be combined with -a/--all-files, --top or --group-by."
+        );
+    }
+
+    if b.absolute && v.relative_to.is_some() {
+        bail!("--absolute and --relative-to cannot be used together.");
+    }
+
+    if b.watch
+        && (limit.is_some()
+            || b.open
+            || b.where_mode
+            || b.pick
+            || b.tree
+            || b.all_drives
+            || top.is_some()
+            || group_by.is_some()
+            || exec.is_some()
+            || b.delete
+            || relocating)
+    {
+        bail!(
+            "--watch only follows a plain search; it cannot be combined with -1/--first, -L/--limit, -o/--open, -w/--where, --pick, --tree, -A/--all-drives, --top, --group-by, -x/--exec, --delete, --copy-to or --move-to."
+        );
+    }
+
+    if b.json
+        && (b.open
+            || b.where_mode
+            || b.pick
+            || b.tree
+            || b.all_drives
+            || top.is_some()
+            || group_by.is_some()
+            || exec.is_some()
+            || b.delete
+            || relocating
+            || b.watch)
+    {
+        bail!(
+            "--json prints the matches themselves; it cannot be combined with -o/--open, -w/--where, --pick, --tree, -A/--all-drives, --top, --group-by, -x/--exec, --delete, --copy-to, --move-to or --watch."
+        );
+    }
+
+    if (v.dirs.len() > 1 || b.all_drives)
+        && (b.open
+            || b.where_mode
+            || b.pick
+            || top.is_some()
+            || group_by.is_some()
+            || exec.is_some()
+            || b.delete
+            || relocating
+            || b.index)
+    {
+        bail!(
+            "Several roots (repeated -d or -A/--all-drives) cannot be combined with -o/--open, -w/--where, --pick, --top, --group-by, -x/--exec, --delete, --copy-to, --move-to or -I/--index."
+        );
+    }
+    if v.dirs.len() > 1 && (b.watch || b.json) {
+        bail!("--watch and --json take a single -d root.");
+    }
+
+    let timeout = v.timeout.as_deref().map(parse_duration).transpose()?;
+    if timeout.is_some() && b.watch {
+        // a timed-out first scan would leave every later rescan cancelled
+        bail!("--timeout cannot be combined with --watch, which runs until Ctrl-C.");
+    }
+
+    if b.index && b.live {
+        bail!("-I/--index and --live cannot be used together.");
+    }
+    // an index answer can be days old; never act on paths it remembers
+    let acting = exec.is_some() || b.delete || relocating;
+    if b.index && acting {
+        bail!(
+            "-I/--index cannot be combined with -x/--exec, --delete, --copy-to or --move-to; they always scan live."
+        );
+    }
+    if b.index && (top.is_some() || group_by.is_some() || b.all_drives) {
+        bail!("-I/--index cannot be combined with --top, --group-by or -A/--all-drives.");
+    }
+
     Ok(ParsedFlags {
         pattern,
-        dir: v.dir,
+        dirs: v.dirs,
         extension: v.extension,
         threads: v.threads,
-        quiet: b.quiet,
+        quiet: b.quiet || b.json,
         stats: b.stats,
         all: b.all,
         verbose: b.verbose,
@@ -452,6 +969,36 @@ pub fn parse_args(config: &LdxConfig) -> Result<ParsedFlags> {
         case_sensitive: b.case_sensitive,
         limit,
         exclude: v.exclude,
+        sort,
+        reverse: b.reverse,
+        top,
+        rank_by,
+        bytes: b.bytes,
+        tree: b.tree,
+        group_by,
+        color,
+        hyperlink,
+        absolute: b.absolute,
+        relative_to: v.relative_to,
+        exec,
+        jobs,
+        delete: b.delete,
+        dry_run: b.dry_run,
+        yes: b.yes,
+        trash: b.trash,
+        copy_to: v.copy_to,
+        move_to: v.move_to,
+        flatten: b.flatten,
+        on_conflict,
+        pick: b.pick,
+        open_with: v.open_with,
+        reveal: b.reveal,
+        cd_file: v.cd_file,
+        index: b.index,
+        live: b.live || acting,
+        watch: b.watch,
+        json: b.json,
+        timeout,
         show_help: false,
         show_version: false,
         show_config: false,
@@ -515,6 +1062,7 @@ mod tests {
             flags,
             custom: HashMap::new(),
             aliases: HashMap::new(),
+            ..Default::default()
         }
     }
 
@@ -667,4 +1215,31 @@ mod tests {
             validate_combos(&None, &Some("rs".into()), false, None, false, false, false).is_ok()
         );
     }
+
+    #[test]
+    fn exec_command_runs_to_semicolon() {
+        let raw: Vec<String> = ["foo", "-x", "echo", "-h", "{/}", ";", "-q"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (rest, exec) = split_exec(raw).unwrap();
+        assert_eq!(rest, ["foo", "-q"]);
+        assert_eq!(exec.unwrap().mode, ExecMode::Each);
+    }
+
+    #[test]
+    fn exec_needs_a_command() {
+        assert!(split_exec(vec!["foo".into(), "-X".into()]).is_err());
+    }
+
+    #[test]
+    fn timeout_durations_take_units() {
+        assert_eq!(parse_duration("5s").unwrap(), Duration::from_secs(5));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("1.5").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("5 parsecs").is_err());
+        assert!(parse_duration("99999999999999999999999h").is_err());
+    }
 }
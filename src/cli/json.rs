@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::cancel::Stop;
+use crate::cli::display::relative_path;
+use crate::search::{ErrorCategory, ScanResult, error_reason};
+
+// ---------------------------------------------------------------------------
+// --json — one object per search, for scripts
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+struct Report<'a> {
+    root: String,
+    matches: usize,
+    files: usize,
+    dirs: usize,
+    duration_ms: u128,
+    /// `"timed_out"` or `"interrupted"` when the walk ended early.
+    stopped: Option<&'static str>,
+    paths: Vec<String>,
+    errors: Vec<ErrorRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct ErrorRecord<'a> {
+    path: Option<String>,
+    category: &'a str,
+    reason: String,
+}
+
+/// Paths are absolute, as `--absolute` would print them, unless
+/// `--relative-to` gives a base.
+pub fn render(
+    result: &ScanResult,
+    root: &Path,
+    relative_to: Option<&Path>,
+    matches: usize,
+    stop: Option<Stop>,
+) -> String {
+    let path_str = |path: &Path| {
+        let path = match relative_to {
+            Some(base) => relative_path(path, base),
+            None => path.to_path_buf(),
+        };
+        if path.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            path.to_string_lossy().into_owned()
+        }
+    };
+    let report = Report {
+        root: path_str(root),
+        matches,
+        files: result.files,
+        dirs: result.dirs,
+        duration_ms: result.duration.as_millis(),
+        stopped: stop.map(|s| match s {
+            Stop::TimedOut => "timed_out",
+            Stop::Interrupted => "interrupted",
+        }),
+        paths: result.paths.iter().map(|p| path_str(p)).collect(),
+        errors: result
+            .errors
+            .iter()
+            .map(|err| ErrorRecord {
+                path: err.path().map(|p| path_str(p)),
+                category: ErrorCategory::of(err).key(),
+                reason: error_reason(err),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn errors_carry_category_and_reason() {
+        let result = ScanResult {
+            matches: 1,
+            files: 3,
+            dirs: 1,
+            duration: Duration::from_millis(12),
+            paths: vec![PathBuf::from("/r/a \"b\".txt")],
+            errors: vec![parex::ParexError::Io {
+                path: PathBuf::from("/r/locked"),
+                source: std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+            }],
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&render(&result, Path::new("/r"), None, 1, None)).unwrap();
+
+        assert_eq!(value["paths"][0], "/r/a \"b\".txt");
+        assert_eq!(value["duration_ms"], 12);
+        assert!(value["stopped"].is_null());
+        assert_eq!(value["errors"][0]["path"], "/r/locked");
+        assert_eq!(value["errors"][0]["category"], "permission_denied");
+        assert_eq!(value["errors"][0]["reason"], "permission denied");
+
+        let value: serde_json::Value = serde_json::from_str(&render(
+            &result,
+            Path::new("/r"),
+            Some(Path::new("/r/sub")),
+            1,
+            None,
+        ))
+        .unwrap();
+        assert_eq!(value["root"], "..");
+        assert_eq!(value["paths"][0], "../a \"b\".txt");
+        assert_eq!(value["errors"][0]["path"], "../locked");
+    }
+}
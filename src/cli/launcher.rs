@@ -1,41 +1,307 @@
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use colored::Colorize;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::flags::ParsedFlags;
+use crate::cli::picker;
+use crate::cli::theme::{success, warning};
+use crate::config::LdxConfig;
+use crate::exec::{CommandTemplate, ExecMode};
 
 // ---------------------------------------------------------------------------
-// Open a file with the OS default handler
+// Opener resolution — --open-with, then [open], then $EDITOR for text files,
+// then the OS default handler
 // ---------------------------------------------------------------------------
 
-pub fn open_file(path: &std::path::Path) -> Result<()> {
-    println!("{} {}", "Launching:".green().bold(), path.display());
+/// Finds the `[open]` command for a file. Keys with `*` or `?` are globs on
+/// the file name (longest first); anything else is an extension.
+fn configured_opener<'a>(table: &'a HashMap<String, String>, path: &Path) -> Option<&'a str> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    let mut globs: Vec<(&String, &String)> = table
+        .iter()
+        .filter(|(k, _)| k.contains(['*', '?']))
+        .collect();
+    globs.sort_by_key(|(k, _)| std::cmp::Reverse(k.len()));
+    if let Some((_, cmd)) = globs
+        .iter()
+        .find(|(k, _)| glob_match(&k.to_lowercase(), &name))
+    {
+        return Some(cmd);
+    }
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    table
+        .iter()
+        .find(|(k, _)| !k.contains(['*', '?']) && k.to_lowercase() == ext)
+        .map(|(_, cmd)| cmd.as_str())
+}
+
+/// `*` matches any run of characters, `?` exactly one.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let (p, n): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        match p.get(pi) {
+            Some('*') => {
+                backtrack = Some((pi, ni));
+                pi += 1;
+            }
+            Some(&c) if c == '?' || c == n[ni] => {
+                pi += 1;
+                ni += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    pi = bp + 1;
+                    ni = bn + 1;
+                    backtrack = Some((bp, bn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+fn is_text_file(path: &Path) -> bool {
+    let mut buf = [0u8; 8192];
+    std::fs::File::open(path)
+        .and_then(|mut f| std::io::Read::read(&mut f, &mut buf))
+        .is_ok_and(|n| !buf[..n].contains(&0))
+}
+
+fn editor() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|v| std::env::var(v).ok().filter(|e| !e.trim().is_empty()))
+}
+
+/// `{+}` in an opener stands for every selected file in one launch; `{}` or
+/// no placeholder launches it once per file.
+const ALL_FILES: &str = "{+}";
+
+fn takes_many(command: &str) -> bool {
+    command.contains(ALL_FILES)
+}
+
+/// Splits a configured command line with shell quoting rules, so quoted
+/// program paths and empty arguments survive, and fills in `{}`-style
+/// placeholders once per path, appending the paths when there are none.
+fn templated(command: &str, paths: &[&Path]) -> Result<Command> {
+    let args = shell_words::split(command)
+        .with_context(|| format!("Opener command {:?} has unbalanced quotes.", command))?;
+    if args.is_empty() {
+        bail!("Opener command is empty.");
+    }
+    let args = args
+        .into_iter()
+        .map(|a| a.replace(ALL_FILES, "{}"))
+        .collect();
+    Ok(CommandTemplate::new(ExecMode::Each, args).command_for(paths))
+}
+
+/// `open` takes any number of files; `xdg-open` and `start` take one.
+const SYSTEM_OPENER_TAKES_MANY: bool = cfg!(target_os = "macos");
+
+fn system_opener(paths: &[&Path]) -> Command {
+    #[cfg(windows)]
+    let cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/c", "start", ""]).args(paths);
+        cmd
+    };
+
+    #[cfg(target_os = "macos")]
+    let cmd = {
+        let mut cmd = Command::new("open");
+        cmd.args(paths);
+        cmd
+    };
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let cmd = {
+        let mut cmd = Command::new("xdg-open");
+        cmd.args(paths);
+        cmd
+    };
+
+    cmd
+}
 
+/// Opens the file manager on the folder holding `path`, selecting it where
+/// the platform allows.
+fn reveal_command(path: &Path) -> Command {
     #[cfg(windows)]
-    std::process::Command::new("cmd")
-        .args(["/c", "start", "", &path.to_string_lossy()])
-        .spawn()?;
+    let cmd = {
+        let mut cmd = Command::new("explorer");
+        cmd.arg(format!("/select,{}", path.display()));
+        cmd
+    };
 
     #[cfg(target_os = "macos")]
-    std::process::Command::new("open").arg(path).spawn()?;
+    let cmd = {
+        let mut cmd = Command::new("open");
+        cmd.arg("-R").arg(path);
+        cmd
+    };
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let cmd = system_opener(&[path.parent().unwrap_or(path)]);
+
+    cmd
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Opener {
+    /// A user command (--open-with, [open], $EDITOR); see [`ALL_FILES`].
+    Template(String),
+    System,
+    Reveal,
+}
+
+fn opener_for(path: &Path, config: &LdxConfig, f: &ParsedFlags) -> Opener {
+    if f.reveal {
+        Opener::Reveal
+    } else if let Some(with) = &f.open_with {
+        Opener::Template(with.clone())
+    } else if let Some(configured) = configured_opener(&config.open, path) {
+        Opener::Template(configured.to_string())
+    } else if let Some(editor) = editor().filter(|_| path.is_file() && is_text_file(path)) {
+        // every editor worth the name opens several files at once
+        Opener::Template(format!("{} {}", editor, ALL_FILES))
+    } else {
+        Opener::System
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Open matches — one invocation per opener when it accepts several files
+// (`{+}` or the OS handler on macOS), otherwise one per file.
+// User-chosen commands run in the foreground so terminal editors work; the
+// OS handler is spawned and left running.
+// ---------------------------------------------------------------------------
+
+/// Ask before opening more than this many files.
+const CONFIRM_ABOVE: usize = 10;
+/// Never open more than this many files in one go.
+const MAX_OPEN: usize = 50;
+
+pub fn open_files(paths: &[PathBuf], config: &LdxConfig, f: &ParsedFlags) -> Result<()> {
+    if paths.len() > MAX_OPEN {
+        bail!(
+            "Refusing to open {} files at once (limit {}). Narrow the search or use -x.",
+            paths.len(),
+            MAX_OPEN
+        );
+    }
+    if paths.len() > CONFIRM_ABOVE {
+        print!(
+            "{} {} files.\n  Continue? [y/N] ",
+            warning("Open:").bold(),
+            paths.len()
+        );
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Open cancelled.");
+            return Ok(());
+        }
+    }
 
-    #[cfg(target_os = "linux")]
-    std::process::Command::new("xdg-open").arg(path).spawn()?;
+    let mut groups: Vec<(Opener, Vec<&Path>)> = Vec::new();
+    for path in paths {
+        let opener = opener_for(path, config, f);
+        match groups.iter_mut().find(|(o, _)| *o == opener) {
+            Some((_, group)) => group.push(path),
+            None => groups.push((opener, vec![path])),
+        }
+    }
 
+    for (opener, group) in groups {
+        match opener {
+            Opener::Template(command) if takes_many(&command) => {
+                launch(templated(&command, &group)?, &group, true)?
+            }
+            Opener::Template(command) => {
+                for path in group {
+                    launch(templated(&command, &[path])?, &[path], true)?;
+                }
+            }
+            Opener::System if SYSTEM_OPENER_TAKES_MANY => {
+                launch(system_opener(&group), &group, false)?
+            }
+            Opener::System => {
+                for path in group {
+                    launch(system_opener(&[path]), &[path], false)?;
+                }
+            }
+            Opener::Reveal => {
+                let mut seen: Vec<&Path> = Vec::new();
+                for path in group {
+                    let parent = path.parent().unwrap_or(path);
+                    if !seen.contains(&parent) {
+                        seen.push(parent);
+                        launch(reveal_command(path), &[path], false)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn launch(mut cmd: Command, paths: &[&Path], wait: bool) -> Result<()> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let what = match paths {
+        [one] => one.display().to_string(),
+        many => format!("{} files", many.len()),
+    };
+    println!(
+        "{} {} {}",
+        success("Launching:").bold(),
+        what,
+        format!("({})", program).dimmed()
+    );
+
+    if wait {
+        let status = cmd
+            .status()
+            .with_context(|| format!("Failed to run {:?}", program))?;
+        if !status.success() {
+            bail!("{} exited with {}", program, status);
+        }
+    } else {
+        cmd.spawn()
+            .with_context(|| format!("Failed to run {:?}", program))?;
+    }
     Ok(())
 }
 
 // ---------------------------------------------------------------------------
-// Interactive picker when multiple matches found
+// Choose among several matches — the TUI picker on a terminal, a numbered
+// prompt otherwise. Empty when the user backs out.
 // ---------------------------------------------------------------------------
 
-pub fn prompt_and_open(paths: &[PathBuf]) -> Result<()> {
+pub fn choose(paths: &[PathBuf], labels: Vec<String>) -> Result<Vec<PathBuf>> {
+    if picker::available() {
+        picker::pick(paths, labels)
+    } else {
+        prompt_choice(paths)
+    }
+}
+
+fn prompt_choice(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
     println!(
         "{}",
-        "Found more than 1 result! Pick one of the following:".yellow()
+        warning("Found more than 1 result! Pick from the following:")
     );
     for (i, path) in paths.iter().enumerate() {
         println!("  [{}] {}", i + 1, path.display());
     }
-    print!("\nEnter number to open (or q to quit): ");
+    print!("\nEnter numbers like 1,3,5-8, a for all, or q to quit: ");
     std::io::Write::flush(&mut std::io::stdout())?;
 
     let mut input = String::new();
@@ -43,17 +309,124 @@ pub fn prompt_and_open(paths: &[PathBuf]) -> Result<()> {
     let input = input.trim();
 
     if input == "q" || input == "Q" {
-        return Ok(());
+        return Ok(Vec::new());
+    }
+
+    let picks = parse_selection(input, paths.len())?;
+    Ok(picks.into_iter().map(|i| paths[i].clone()).collect())
+}
+
+/// Parses `1,3,5-8` or `a` into zero-based indices, in order, without
+/// duplicates.
+fn parse_selection(input: &str, len: usize) -> Result<Vec<usize>> {
+    if input.eq_ignore_ascii_case("a") {
+        return Ok((0..len).collect());
     }
+    let invalid = || anyhow::anyhow!("Invalid selection {:?}. Run ldx again to try.", input);
+    let number = |s: &str| match s.trim().parse::<usize>() {
+        Ok(n) if n >= 1 && n <= len => Ok(n - 1),
+        _ => Err(invalid()),
+    };
 
-    match input.parse::<usize>() {
-        Ok(n) if n >= 1 && n <= paths.len() => {
-            open_file(&paths[n - 1])?;
+    let mut picks: Vec<usize> = Vec::new();
+    for part in input.split(',').filter(|p| !p.trim().is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((a, b)) => (number(a)?, number(b)?),
+            None => {
+                let n = number(part)?;
+                (n, n)
+            }
+        };
+        if start > end {
+            return Err(invalid());
         }
-        _ => {
-            bail!("Invalid selection. Run ldx again to try.");
+        for i in start..=end {
+            if !picks.contains(&i) {
+                picks.push(i);
+            }
         }
     }
+    if picks.is_empty() {
+        return Err(invalid());
+    }
+    Ok(picks)
+}
 
-    Ok(())
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_names() {
+        assert!(glob_match("*.test.js", "app.test.js"));
+        assert!(glob_match("makefile", "makefile"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("*.test.js", "app.js"));
+        assert!(glob_match("*a*b", "xaxxb"));
+    }
+
+    #[test]
+    fn opener_prefers_globs_over_extensions() {
+        let table: HashMap<String, String> = [
+            ("js", "node {}"),
+            ("*.test.js", "jest {}"),
+            ("PDF", "zathura {}"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(
+            configured_opener(&table, Path::new("/r/a.test.js")),
+            Some("jest {}")
+        );
+        assert_eq!(
+            configured_opener(&table, Path::new("/r/a.js")),
+            Some("node {}")
+        );
+        assert_eq!(
+            configured_opener(&table, Path::new("/r/doc.pdf")),
+            Some("zathura {}")
+        );
+        assert_eq!(configured_opener(&table, Path::new("/r/Makefile")), None);
+    }
+
+    #[test]
+    fn opener_commands_keep_quoted_arguments() {
+        let cmd = templated(
+            r#""/Applications/Sublime Text.app/subl" -a '' --wait"#,
+            &[Path::new("/r/my file.rs")],
+        )
+        .unwrap();
+        assert_eq!(cmd.get_program(), "/Applications/Sublime Text.app/subl");
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args, ["-a", "", "--wait", "/r/my file.rs"]);
+        assert!(templated("code 'unclosed", &[]).is_err());
+    }
+
+    #[test]
+    fn all_files_placeholder_takes_every_path() {
+        let paths = [Path::new("/r/a.png"), Path::new("/r/b.png")];
+        let cmd = templated("feh -F {+}", &paths).unwrap();
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            ["-F", "/r/a.png", "/r/b.png"]
+        );
+        assert!(takes_many("nvim -p {+}"));
+        assert!(!takes_many("mpv --loop {}"));
+        assert!(!takes_many("zathura"));
+    }
+
+    #[test]
+    fn parses_lists_ranges_and_all() {
+        assert_eq!(parse_selection("1,3,5-8", 10).unwrap(), [0, 2, 4, 5, 6, 7]);
+        assert_eq!(parse_selection("2, 1-2", 3).unwrap(), [1, 0]);
+        assert_eq!(parse_selection("a", 3).unwrap(), [0, 1, 2]);
+        assert!(parse_selection("4", 3).is_err());
+        assert!(parse_selection("3-1", 3).is_err());
+        assert!(parse_selection("x", 3).is_err());
+    }
 }
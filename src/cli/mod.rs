@@ -1,3 +1,8 @@
 pub mod display;
 pub mod flags;
+pub mod json;
 pub mod launcher;
+pub mod picker;
+pub mod shell;
+pub mod theme;
+pub mod tree;
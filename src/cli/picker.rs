@@ -0,0 +1,338 @@
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use std::collections::BTreeSet;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+// ---------------------------------------------------------------------------
+// Picker state — kept free of terminal I/O so filtering is testable
+// ---------------------------------------------------------------------------
+
+struct Picker {
+    labels: Vec<String>,
+    query: String,
+    /// Indices into `labels` that survive the current query.
+    visible: Vec<usize>,
+    /// Position within `visible`.
+    cursor: usize,
+    /// First `visible` row on screen.
+    scroll: usize,
+    selected: BTreeSet<usize>,
+}
+
+impl Picker {
+    fn new(labels: Vec<String>) -> Self {
+        let visible = (0..labels.len()).collect();
+        Self {
+            labels,
+            query: String::new(),
+            visible,
+            cursor: 0,
+            scroll: 0,
+            selected: BTreeSet::new(),
+        }
+    }
+
+    /// Every whitespace-separated term must appear, case-insensitively.
+    fn refilter(&mut self) {
+        let terms: Vec<String> = self
+            .query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        self.visible = self
+            .labels
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| {
+                let label = label.to_lowercase();
+                terms.iter().all(|t| label.contains(t.as_str()))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.visible.get(self.cursor).copied()
+    }
+
+    fn step(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let last = self.visible.len() as isize - 1;
+        self.cursor = (self.cursor as isize + delta).clamp(0, last) as usize;
+    }
+
+    fn toggle(&mut self) {
+        if let Some(i) = self.current()
+            && !self.selected.remove(&i)
+        {
+            self.selected.insert(i);
+        }
+    }
+
+    /// The marked entries, or the highlighted one when nothing is marked.
+    fn chosen(&self) -> Vec<usize> {
+        if self.selected.is_empty() {
+            self.current().into_iter().collect()
+        } else {
+            self.selected.iter().copied().collect()
+        }
+    }
+
+    fn scroll_into_view(&mut self, rows: usize) {
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if rows > 0 && self.cursor >= self.scroll + rows {
+            self.scroll = self.cursor + 1 - rows;
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Preview — head of a file or a directory listing
+// ---------------------------------------------------------------------------
+
+fn preview(path: &Path, max_lines: usize) -> Vec<String> {
+    if path.is_dir() {
+        let mut names: Vec<String> = match std::fs::read_dir(path) {
+            Ok(rd) => rd
+                .flatten()
+                .map(|e| {
+                    let name = e.file_name().to_string_lossy().into_owned();
+                    if e.file_type().is_ok_and(|t| t.is_dir()) {
+                        format!("{}/", name)
+                    } else {
+                        name
+                    }
+                })
+                .collect(),
+            Err(e) => return vec![format!("({})", e)],
+        };
+        names.sort();
+        if names.is_empty() {
+            names.push("(empty directory)".into());
+        }
+        names.truncate(max_lines);
+        return names;
+    }
+
+    let mut buf = vec![0u8; 16 * 1024];
+    let read = std::fs::File::open(path).and_then(|mut f| std::io::Read::read(&mut f, &mut buf));
+    match read {
+        Err(e) => vec![format!("({})", e)],
+        Ok(n) if buf[..n].contains(&0) => vec!["(binary file)".into()],
+        Ok(n) => String::from_utf8_lossy(&buf[..n])
+            .lines()
+            .take(max_lines)
+            .map(|l| l.replace('\t', "    "))
+            .collect(),
+    }
+}
+
+fn clip(s: &str, width: usize) -> String {
+    s.chars().filter(|c| !c.is_control()).take(width).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Terminal session — raw mode + alternate screen on stderr, so stdout stays
+// free for whatever the selection is piped into
+// ---------------------------------------------------------------------------
+
+struct Session;
+
+impl Session {
+    fn start() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            std::io::stderr(),
+            terminal::EnterAlternateScreen,
+            cursor::Hide
+        )?;
+        Ok(Session)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = execute!(
+            std::io::stderr(),
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// The picker needs a keyboard and somewhere to draw.
+pub fn available() -> bool {
+    std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+fn draw(out: &mut impl Write, p: &mut Picker, paths: &[PathBuf]) -> Result<()> {
+    let (cols, rows) = terminal::size()?;
+    let (cols, rows) = (cols as usize, rows as usize);
+    let list_rows = rows.saturating_sub(2);
+    let list_width = if cols >= 80 { cols / 2 } else { cols };
+    p.scroll_into_view(list_rows);
+
+    queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    queue!(
+        out,
+        Print(clip(&format!("> {}", p.query), cols)),
+        cursor::MoveTo(0, 1),
+        SetAttribute(Attribute::Dim),
+        Print(clip(
+            &format!(
+                "  {}/{}  {} selected — ↑↓ move · Tab mark · Enter accept · Esc cancel",
+                p.visible.len(),
+                p.labels.len(),
+                p.selected.len()
+            ),
+            cols
+        )),
+        SetAttribute(Attribute::Reset)
+    )?;
+
+    for (row, &i) in p.visible.iter().skip(p.scroll).take(list_rows).enumerate() {
+        let here = p.scroll + row == p.cursor;
+        let mark = if p.selected.contains(&i) { "●" } else { " " };
+        queue!(out, cursor::MoveTo(0, (row + 2) as u16))?;
+        if here {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+        }
+        let line = format!("{} {}", mark, p.labels[i]);
+        queue!(
+            out,
+            Print(format!(
+                "{:<w$}",
+                clip(&line, list_width.saturating_sub(1)),
+                w = list_width.saturating_sub(1)
+            )),
+            SetAttribute(Attribute::Reset)
+        )?;
+    }
+
+    if list_width < cols
+        && let Some(i) = p.current()
+    {
+        let pane = cols - list_width - 2;
+        for (row, line) in preview(&paths[i], list_rows).iter().enumerate() {
+            queue!(
+                out,
+                cursor::MoveTo(list_width as u16, (row + 2) as u16),
+                SetAttribute(Attribute::Dim),
+                Print("│ "),
+                SetAttribute(Attribute::Reset),
+                Print(clip(line, pane))
+            )?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// pick — returns the chosen paths; empty when cancelled
+// ---------------------------------------------------------------------------
+
+pub fn pick(paths: &[PathBuf], labels: Vec<String>) -> Result<Vec<PathBuf>> {
+    let mut p = Picker::new(labels);
+    let _session = Session::start()?;
+    let mut out = std::io::stderr();
+
+    loop {
+        draw(&mut out, &mut p, paths)?;
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let page = terminal::size()?.1.saturating_sub(2).max(1) as isize;
+        match code {
+            KeyCode::Esc => return Ok(Vec::new()),
+            KeyCode::Char('c') | KeyCode::Char('g') if ctrl => return Ok(Vec::new()),
+            KeyCode::Enter => {
+                return Ok(p.chosen().into_iter().map(|i| paths[i].clone()).collect());
+            }
+            KeyCode::Up => p.step(-1),
+            KeyCode::Char('p') if ctrl => p.step(-1),
+            KeyCode::Down => p.step(1),
+            KeyCode::Char('n') if ctrl => p.step(1),
+            KeyCode::PageUp => p.step(-page),
+            KeyCode::PageDown => p.step(page),
+            KeyCode::Home => p.cursor = 0,
+            KeyCode::End => p.step(isize::MAX / 2),
+            KeyCode::Tab => {
+                p.toggle();
+                p.step(1);
+            }
+            KeyCode::BackTab => {
+                p.toggle();
+                p.step(-1);
+            }
+            KeyCode::Backspace if p.query.pop().is_some() => p.refilter(),
+            KeyCode::Char('u') if ctrl => {
+                p.query.clear();
+                p.refilter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                p.query.push(c);
+                p.refilter();
+            }
+            _ => {}
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picker() -> Picker {
+        Picker::new(
+            ["src/main.rs", "src/cli/picker.rs", "README.md"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn every_query_term_must_match() {
+        let mut p = picker();
+        p.query = "SRC rs".into();
+        p.refilter();
+        assert_eq!(p.visible, [0, 1]);
+        p.query = "src pick".into();
+        p.refilter();
+        assert_eq!(p.visible, [1]);
+    }
+
+    #[test]
+    fn marks_survive_refiltering() {
+        let mut p = picker();
+        p.step(2);
+        p.toggle();
+        p.query = "main".into();
+        p.refilter();
+        assert_eq!(p.chosen(), [2]);
+        p.selected.clear();
+        assert_eq!(p.chosen(), [0]);
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{Result, bail};
+use std::str::FromStr;
+
+// ---------------------------------------------------------------------------
+// `ldx init <shell>` — a wrapper function that makes -w change directory.
+//
+// The wrapper hands ldx a temp file via --cd-file; in -w mode ldx writes
+// only the target directory there instead of printing the cd hint, and the
+// wrapper cds into it. Normal output is untouched, so aliases that expand to
+// -w (e.g. `ldx repo`) become jump commands.
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Pwsh,
+}
+
+impl FromStr for Shell {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            "powershell" | "pwsh" => Ok(Shell::Pwsh),
+            _ => bail!(
+                "Unknown shell: {:?}. Expected bash, zsh, fish or powershell.",
+                s
+            ),
+        }
+    }
+}
+
+const POSIX: &str = r#"# ldx shell integration — add to your shell rc:
+#   eval "$(ldx init SHELL)"
+ldx() {
+    local __ldx_tmp __ldx_code __ldx_dir
+    __ldx_tmp="$(mktemp)" || return 1
+    command ldx --cd-file "$__ldx_tmp" "$@"
+    __ldx_code=$?
+    __ldx_dir="$(cat -- "$__ldx_tmp")"
+    rm -f -- "$__ldx_tmp"
+    if [ -n "$__ldx_dir" ] && [ -d "$__ldx_dir" ]; then
+        cd -- "$__ldx_dir" || return 1
+    fi
+    return $__ldx_code
+}
+"#;
+
+const FISH: &str = r#"# ldx shell integration — add to ~/.config/fish/config.fish:
+#   ldx init fish | source
+function ldx
+    set -l tmp (mktemp); or return 1
+    command ldx --cd-file $tmp $argv
+    set -l code $status
+    set -l dir (cat -- $tmp)
+    rm -f -- $tmp
+    if test -n "$dir"; and test -d "$dir"
+        cd -- $dir; or return 1
+    end
+    return $code
+end
+"#;
+
+const POWERSHELL: &str = r#"# ldx shell integration — add to $PROFILE:
+#   Invoke-Expression (& ldx init powershell | Out-String)
+function ldx {
+    $tmp = [System.IO.Path]::GetTempFileName()
+    $exe = (Get-Command ldx -CommandType Application | Select-Object -First 1).Source
+    & $exe --cd-file $tmp @args
+    $code = $LASTEXITCODE
+    $dir = (Get-Content -Raw -LiteralPath $tmp -ErrorAction SilentlyContinue)
+    Remove-Item -LiteralPath $tmp -ErrorAction SilentlyContinue
+    if ($dir -and (Test-Path -LiteralPath $dir.Trim() -PathType Container)) {
+        Set-Location -LiteralPath $dir.Trim()
+    }
+    $global:LASTEXITCODE = $code
+}
+"#;
+
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => POSIX.replace("SHELL", "bash"),
+        Shell::Zsh => POSIX.replace("SHELL", "zsh"),
+        Shell::Fish => FISH.to_string(),
+        Shell::Pwsh => POWERSHELL.to_string(),
+    }
+}
+
+pub fn run(args: &[String]) -> Result<()> {
+    let Some(name) = args.first() else {
+        bail!("Usage: ldx init bash|zsh|fish|powershell");
+    };
+    print!("{}", script(name.parse()?));
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_script_passes_cd_file_to_the_binary() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::Pwsh] {
+            assert!(script(shell).contains("--cd-file"), "{:?}", shell);
+        }
+        assert!(script(Shell::Zsh).contains("ldx init zsh"));
+        assert!("tcsh".parse::<Shell>().is_err());
+    }
+}
@@ -0,0 +1,444 @@
+use anyhow::{Result, bail};
+use colored::{Color, ColoredString, Colorize};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parex::EntryKind;
+
+use crate::config::ThemeConfig;
+
+// ---------------------------------------------------------------------------
+// --color / --hyperlink auto|always|never
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum When {
+    /// Decide from the environment — stdout is a terminal, NO_COLOR, etc.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for When {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(When::Auto),
+            "always" => Ok(When::Always),
+            "never" => Ok(When::Never),
+            _ => bail!("Invalid value {:?}. Expected auto, always or never.", s),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Theme — UI colors from [theme], entry colors from LS_COLORS
+// ---------------------------------------------------------------------------
+
+pub struct Theme {
+    pub path: Color,
+    pub highlight: Color,
+    pub accent: Color,
+    pub label: Color,
+    pub warning: Color,
+    pub success: Color,
+    ls_colors: Option<LsColors>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            path: Color::BrightCyan,
+            highlight: Color::BrightYellow,
+            accent: Color::BrightCyan,
+            label: Color::Cyan,
+            warning: Color::Yellow,
+            success: Color::Green,
+            ls_colors: None,
+        }
+    }
+}
+
+/// Parses a theme color: any `colored` name ("bright cyan", "bright_cyan")
+/// or a `#rrggbb` hex value.
+pub fn parse_color(s: &str) -> Option<Color> {
+    Color::from_str(&s.replace('_', " ")).ok()
+}
+
+impl Theme {
+    pub fn from_config(config: &ThemeConfig) -> Self {
+        let base = Theme::default();
+        let pick = |value: &Option<String>, fallback: Color| {
+            value.as_deref().and_then(parse_color).unwrap_or(fallback)
+        };
+        Self {
+            path: pick(&config.path, base.path),
+            highlight: pick(&config.highlight, base.highlight),
+            accent: pick(&config.accent, base.accent),
+            label: pick(&config.label, base.label),
+            warning: pick(&config.warning, base.warning),
+            success: pick(&config.success, base.success),
+            ls_colors: if config.ls_colors.unwrap_or(true) {
+                std::env::var("LS_COLORS").ok().map(|v| LsColors::parse(&v))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+static HYPERLINKS: AtomicBool = AtomicBool::new(false);
+
+/// Installs the theme and applies the color and hyperlink choices.
+/// Call once, early in main.
+pub fn init(config: &ThemeConfig, color: When, hyperlink: When) {
+    match color {
+        When::Always => colored::control::set_override(true),
+        When::Never => colored::control::set_override(false),
+        When::Auto => {}
+    }
+    let links = match hyperlink {
+        When::Always => true,
+        When::Never => false,
+        When::Auto => hyperlink_capable(),
+    };
+    HYPERLINKS.store(links, Ordering::Relaxed);
+    let _ = THEME.set(Theme::from_config(config));
+}
+
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+fn enabled() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+// ── Role helpers ──────────────────────────────────────────────────────────────
+
+pub fn accent(s: &str) -> ColoredString {
+    s.color(theme().accent)
+}
+
+pub fn label(s: &str) -> ColoredString {
+    s.color(theme().label)
+}
+
+pub fn warning(s: &str) -> ColoredString {
+    s.color(theme().warning)
+}
+
+pub fn success(s: &str) -> ColoredString {
+    s.color(theme().success)
+}
+
+pub fn highlight(s: &str) -> ColoredString {
+    s.color(theme().highlight).bold()
+}
+
+// ---------------------------------------------------------------------------
+// Entry styling — LS_COLORS when available, [theme].path otherwise
+// ---------------------------------------------------------------------------
+
+/// Paints the file-name part of an entry according to its type/extension.
+pub fn paint_name(text: &str, path: &Path) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    paint_styled(text, name_style(path, None))
+}
+
+/// The LS_COLORS code for `path`. With the entry's `kind` from the walk only
+/// files are statted, and only when LS_COLORS styles permission bits; with
+/// `None` the entry is looked up. Callers painting one name in several pieces
+/// resolve it once; without colors or LS_COLORS nothing is read.
+pub fn name_style(path: &Path, kind: Option<&EntryKind>) -> Option<&'static str> {
+    if !enabled() {
+        return None;
+    }
+    theme().ls_colors.as_ref()?.style_for(path, kind)
+}
+
+/// Paints part of a name with a style from [`name_style`].
+pub fn paint_styled(text: &str, style: Option<&str>) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    match style {
+        Some(code) => sgr(code, text),
+        None => text.color(theme().path).to_string(),
+    }
+}
+
+/// Paints the leading directory part of a displayed path.
+pub fn paint_parent(text: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    match theme().ls_colors.as_ref().and_then(|ls| ls.types.get("di")) {
+        Some(code) if enabled() => sgr(code, text),
+        _ => text.color(theme().path).to_string(),
+    }
+}
+
+fn sgr(code: &str, text: &str) -> String {
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+// ---------------------------------------------------------------------------
+// OSC 8 hyperlinks — ctrl-click a result to open it
+// ---------------------------------------------------------------------------
+
+/// Only an interactive terminal can act on OSC 8; pipes and `TERM=dumb`
+/// would just receive escape noise.
+fn hyperlink_capable() -> bool {
+    std::io::stdout().is_terminal() && std::env::var("TERM").map(|t| t != "dumb").unwrap_or(true)
+}
+
+/// Wraps already-painted `text` in a `file://` link to `path` when enabled.
+pub fn hyperlink(text: &str, path: &Path) -> String {
+    if !HYPERLINKS.load(Ordering::Relaxed) {
+        return text.to_string();
+    }
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", file_url(path), text)
+}
+
+fn file_url(path: &Path) -> String {
+    file_url_on(hostname(), path)
+}
+
+/// Names the host, as `ls` and `fd` do, so a terminal reached over SSH does
+/// not open the path on the machine it runs on.
+fn file_url_on(host: &str, path: &Path) -> String {
+    let abs = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let raw = abs.to_string_lossy().replace('\\', "/");
+    let sep = if raw.starts_with('/') { "" } else { "/" };
+    format!(
+        "file://{}{}{}",
+        percent_encode(host),
+        sep,
+        percent_encode(&raw)
+    )
+}
+
+/// Looked up once; empty (a local URL) if the name cannot be read.
+fn hostname() -> &'static str {
+    static HOST: OnceLock<String> = OnceLock::new();
+    HOST.get_or_init(read_hostname)
+}
+
+#[cfg(unix)]
+fn read_hostname() -> String {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length, and one byte is kept
+    // back so the name is always NUL-terminated.
+    let rc = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len() - 1) };
+    if rc != 0 {
+        return String::new();
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(0);
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+#[cfg(not(unix))]
+fn read_hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_default()
+}
+
+/// URI-escapes everything but unreserved characters, `/` and `:`.
+pub fn percent_encode(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for b in raw.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// LS_COLORS parsing
+// ---------------------------------------------------------------------------
+
+struct LsColors {
+    types: HashMap<String, String>,
+    /// `*suffix=code` entries, suffix lowercased, longest first.
+    suffixes: Vec<(String, String)>,
+    /// Whether any of `su`, `sg` or `ex` is set, so files need a stat.
+    mode_styles: bool,
+}
+
+/// Permission-based file types, in the order `ls` prefers them.
+const MODE_KINDS: [&str; 3] = ["su", "sg", "ex"];
+
+impl LsColors {
+    fn parse(raw: &str) -> Self {
+        let mut types = HashMap::new();
+        let mut suffixes = Vec::new();
+        for item in raw.split(':') {
+            let Some((key, code)) = item.split_once('=') else {
+                continue;
+            };
+            if code.is_empty() {
+                continue;
+            }
+            match key.strip_prefix('*') {
+                Some(suffix) => suffixes.push((suffix.to_lowercase(), code.to_string())),
+                None => {
+                    types.insert(key.to_string(), code.to_string());
+                }
+            }
+        }
+        suffixes.sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+        let mode_styles = MODE_KINDS.iter().any(|k| types.contains_key(*k));
+        Self {
+            types,
+            suffixes,
+            mode_styles,
+        }
+    }
+
+    fn style_for(&self, path: &Path, kind: Option<&EntryKind>) -> Option<&str> {
+        let (kind, is_file) = match kind {
+            Some(EntryKind::Dir) => ("di", false),
+            Some(EntryKind::Symlink) => ("ln", false),
+            Some(EntryKind::File) if !self.mode_styles => ("fi", true),
+            _ => self.stat_kind(path)?,
+        };
+
+        if kind != "fi"
+            && let Some(code) = self.types.get(kind)
+        {
+            return Some(code);
+        }
+        if is_file {
+            let name = path.file_name()?.to_string_lossy().to_lowercase();
+            if let Some((_, code)) = self.suffixes.iter().find(|(s, _)| name.ends_with(s)) {
+                return Some(code);
+            }
+        }
+        self.types.get("fi").map(String::as_str)
+    }
+
+    /// The type code for an entry the walk did not describe, and whether it
+    /// is a regular file (which suffixes apply to).
+    fn stat_kind(&self, path: &Path) -> Option<(&'static str, bool)> {
+        let meta = std::fs::symlink_metadata(path).ok()?;
+        let ft = meta.file_type();
+        Some(if ft.is_symlink() {
+            ("ln", false)
+        } else if ft.is_dir() {
+            ("di", false)
+        } else if ft.is_file() {
+            let mode = MODE_KINDS
+                .into_iter()
+                .find(|k| self.types.contains_key(*k) && has_mode(&meta, k));
+            (mode.unwrap_or("fi"), true)
+        } else {
+            (special_kind(&ft).unwrap_or("fi"), false)
+        })
+    }
+}
+
+#[cfg(unix)]
+fn has_mode(meta: &std::fs::Metadata, kind: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    let bits = match kind {
+        "su" => 0o4000,
+        "sg" => 0o2000,
+        _ => 0o111,
+    };
+    meta.permissions().mode() & bits != 0
+}
+
+#[cfg(not(unix))]
+fn has_mode(_meta: &std::fs::Metadata, _kind: &str) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn special_kind(ft: &std::fs::FileType) -> Option<&'static str> {
+    use std::os::unix::fs::FileTypeExt;
+    if ft.is_fifo() {
+        Some("pi")
+    } else if ft.is_socket() {
+        Some("so")
+    } else if ft.is_block_device() {
+        Some("bd")
+    } else if ft.is_char_device() {
+        Some("cd")
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn special_kind(_ft: &std::fs::FileType) -> Option<&'static str> {
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ls_colors_types_and_suffixes() {
+        let ls = LsColors::parse("di=01;34:ln=01;36:*.tar=01;31:*.tar.gz=01;35:bogus");
+        assert_eq!(ls.types.get("di").map(String::as_str), Some("01;34"));
+        assert_eq!(ls.suffixes[0].0, ".tar.gz");
+        assert_eq!(ls.suffixes.len(), 2);
+    }
+
+    #[test]
+    fn known_kinds_are_styled_without_a_stat() {
+        let ls = LsColors::parse("di=01;34:fi=0:*.pdf=01;31");
+        // the path does not exist, so any stat would come back empty
+        let path = Path::new("/nonexistent/ldx/report.pdf");
+        assert_eq!(ls.style_for(path, Some(&EntryKind::Dir)), Some("01;34"));
+        assert_eq!(ls.style_for(path, Some(&EntryKind::File)), Some("01;31"));
+        assert_eq!(ls.style_for(path, None), None);
+
+        let ls = LsColors::parse("ex=01;32:*.pdf=01;31");
+        assert_eq!(ls.style_for(path, Some(&EntryKind::File)), None);
+    }
+
+    #[test]
+    fn theme_colors_accept_names_and_hex() {
+        assert_eq!(parse_color("bright_cyan"), Some(Color::BrightCyan));
+        assert_eq!(parse_color("bright cyan"), Some(Color::BrightCyan));
+        assert!(matches!(
+            parse_color("#ff8800"),
+            Some(Color::TrueColor { .. })
+        ));
+        assert_eq!(parse_color("chartreuse"), None);
+    }
+
+    #[test]
+    fn parses_when() {
+        assert_eq!("never".parse::<When>().unwrap(), When::Never);
+        assert!("sometimes".parse::<When>().is_err());
+    }
+
+    #[test]
+    fn file_url_percent_encodes() {
+        #[cfg(unix)]
+        assert_eq!(
+            file_url_on("box", Path::new("/tmp/my report#1.pdf")),
+            "file://box/tmp/my%20report%231.pdf"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use colored::Colorize;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::cancel::Budget;
+use crate::cli::display::paint_match;
+use crate::cli::theme::{hyperlink, paint_parent};
+use crate::search::SpanMatcher;
+
+// ---------------------------------------------------------------------------
+// Tree node — children keep first-seen order so --sort carries through
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+struct Node {
+    children: Vec<(OsString, Node)>,
+    index: HashMap<OsString, usize>,
+    matched: bool,
+}
+
+impl Node {
+    fn child(&mut self, name: OsString) -> &mut Node {
+        let idx = match self.index.get(&name) {
+            Some(&i) => i,
+            None => {
+                self.index.insert(name.clone(), self.children.len());
+                self.children.push((name, Node::default()));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[idx].1
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rendered line — kept separate from printing so layout is testable
+// ---------------------------------------------------------------------------
+
+pub struct TreeLine {
+    pub path: PathBuf,
+    pub prefix: String,
+    pub name: String,
+    pub matched: bool,
+}
+
+pub fn render_tree(paths: &[PathBuf], root: &Path) -> Vec<TreeLine> {
+    let mut top = Node::default();
+    for path in paths {
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        let mut node = &mut top;
+        for comp in rel.components() {
+            node = node.child(comp.as_os_str().to_os_string());
+        }
+        node.matched = true;
+    }
+
+    let mut lines = vec![TreeLine {
+        path: root.to_path_buf(),
+        prefix: String::new(),
+        name: ".".into(),
+        matched: top.matched,
+    }];
+    walk(&top, root, "", &mut lines);
+    lines
+}
+
+fn walk(node: &Node, dir: &Path, indent: &str, lines: &mut Vec<TreeLine>) {
+    let last = node.children.len().saturating_sub(1);
+    for (i, (name, child)) in node.children.iter().enumerate() {
+        let (branch, next) = if i == last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        let path = dir.join(name);
+        lines.push(TreeLine {
+            path: path.clone(),
+            prefix: format!("{}{}", indent, branch),
+            name: name.to_string_lossy().into_owned(),
+            matched: child.matched,
+        });
+        walk(child, &path, &format!("{}{}", indent, next), lines);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Print — matches highlighted, ancestor directories plain
+// ---------------------------------------------------------------------------
+
+pub fn print_tree(
+    paths: &[PathBuf],
+    root: &Path,
+    root_label: &str,
+    matcher: &dyn SpanMatcher,
+    budget: &Budget,
+) {
+    for mut line in render_tree(paths, root) {
+        if budget.interrupted() {
+            return;
+        }
+        if line.prefix.is_empty() {
+            line.name = root_label.to_string();
+        }
+        let name = if line.matched {
+            hyperlink(
+                &paint_match(&line.name, &line.path, None, matcher),
+                &line.path,
+            )
+        } else {
+            paint_parent(&line.name)
+        };
+        if line.prefix.is_empty() {
+            println!("{}", name);
+        } else {
+            println!("{}{}", line.prefix.dimmed(), name);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(lines: &[TreeLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| format!("{}{}{}", l.prefix, l.name, if l.matched { "*" } else { "" }))
+            .collect()
+    }
+
+    #[test]
+    fn groups_matches_under_shared_parents() {
+        let root = Path::new("/r");
+        let paths: Vec<PathBuf> = ["/r/a/x.rs", "/r/a/b/y.rs", "/r/z.rs"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(
+            plain(&render_tree(&paths, root)),
+            vec![
+                ".",
+                "├── a",
+                "│   ├── x.rs*",
+                "│   └── b",
+                "│       └── y.rs*",
+                "└── z.rs*",
+            ]
+        );
+    }
+
+    #[test]
+    fn matched_directory_keeps_its_children() {
+        let root = Path::new("/r");
+        let paths: Vec<PathBuf> = ["/r/src", "/r/src/main.rs"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(
+            plain(&render_tree(&paths, root)),
+            vec![".", "└── src*", "    └── main.rs*"]
+        );
+    }
+}
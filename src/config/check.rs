@@ -1,24 +1,24 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
 use std::collections::HashMap;
 
+use crate::cli::theme::{accent, label, parse_color, success, warning};
 use crate::config::{DEFAULT_CONFIG, LdxConfig, config_path, is_flag_available};
 
 // ---------------------------------------------------------------------------
 // --check: validate config and print a summary
 // ---------------------------------------------------------------------------
 
-pub fn check_config(config: &LdxConfig) {
+pub fn check_config(config: &LdxConfig) -> Result<()> {
     let path = config_path();
     println!(
         "{}",
-        "── Config Check ─────────────────────────────────"
-            .bright_cyan()
-            .bold()
+        accent("── Config Check ─────────────────────────────────").bold()
     );
     println!("  Path    : {}", path.display());
 
-    let defaults: LdxConfig = toml::from_str(DEFAULT_CONFIG).expect("DEFAULT_CONFIG is valid TOML");
+    let defaults: LdxConfig =
+        toml::from_str(DEFAULT_CONFIG).context("built-in default config is not valid TOML")?;
     let default_targets: Vec<&str> = defaults
         .flags
         .values()
@@ -39,7 +39,7 @@ pub fn check_config(config: &LdxConfig) {
         if let Some(prior) = seen_shorts.get(flag.short.as_str()) {
             println!(
                 "    {} short '-{}' on [flags.{}] already used by [flags.{}]",
-                "WARN".yellow().bold(),
+                warning("WARN").bold(),
                 flag.short,
                 key,
                 prior
@@ -52,7 +52,7 @@ pub fn check_config(config: &LdxConfig) {
         if let Some(prior) = seen_longs.get(flag.long.as_str()) {
             println!(
                 "    {} long '--{}' on [flags.{}] already used by [flags.{}]",
-                "WARN".yellow().bold(),
+                warning("WARN").bold(),
                 flag.long,
                 key,
                 prior
@@ -65,7 +65,7 @@ pub fn check_config(config: &LdxConfig) {
         if flag.target.is_none() && flag.action.as_deref() != Some("show_help") {
             println!(
                 "    {} [flags.{}] has no 'target' — flag will be ignored",
-                "WARN".yellow().bold(),
+                warning("WARN").bold(),
                 key
             );
             warnings += 1;
@@ -77,7 +77,7 @@ pub fn check_config(config: &LdxConfig) {
         {
             println!(
                 "    {} [flags.{}] target '{}' is not a known internal target",
-                "WARN".yellow().bold(),
+                warning("WARN").bold(),
                 key,
                 target
             );
@@ -101,7 +101,7 @@ pub fn check_config(config: &LdxConfig) {
     } else {
         println!("  {} {} defined", "Aliases:".bold(), config.aliases.len());
         for (name, expansion) in &config.aliases {
-            println!("    {} → {}", name.cyan(), expansion.dimmed());
+            println!("    {} → {}", label(name), expansion.dimmed());
         }
     }
 
@@ -119,7 +119,7 @@ pub fn check_config(config: &LdxConfig) {
             if flag.action.is_none() {
                 println!(
                     "    {} [custom.{}] has no 'action' field",
-                    "WARN".yellow().bold(),
+                    warning("WARN").bold(),
                     key
                 );
                 warnings += 1;
@@ -127,7 +127,7 @@ pub fn check_config(config: &LdxConfig) {
             if flag.target.is_none() {
                 println!(
                     "    {} [custom.{}] has no 'target' field",
-                    "WARN".yellow().bold(),
+                    warning("WARN").bold(),
                     key
                 );
                 warnings += 1;
@@ -139,7 +139,7 @@ pub fn check_config(config: &LdxConfig) {
                 flag.short,
                 flag.long,
                 flag.description.dimmed(),
-                target.cyan(),
+                label(target),
                 if value.is_empty() {
                     String::new()
                 } else {
@@ -149,6 +149,76 @@ pub fn check_config(config: &LdxConfig) {
         }
     }
 
+    // ── Openers ──
+    println!();
+    if config.open.is_empty() {
+        println!("  {} none defined (system default)", "Openers:".bold());
+    } else {
+        println!("  {} {} defined", "Openers:".bold(), config.open.len());
+        for (key, command) in &config.open {
+            if command.trim().is_empty() {
+                println!(
+                    "    {} [open] {} has an empty command",
+                    warning("WARN").bold(),
+                    key
+                );
+                warnings += 1;
+            } else if shell_words::split(command).is_err() {
+                println!(
+                    "    {} [open] {} has unbalanced quotes",
+                    warning("WARN").bold(),
+                    key
+                );
+                warnings += 1;
+            } else {
+                println!("    {} → {}", label(key), command.dimmed());
+            }
+        }
+    }
+
+    // ── Index ──
+    println!();
+    let roots = config
+        .index
+        .roots
+        .as_ref()
+        .map(|r| r.join(", "))
+        .unwrap_or_else(|| "~".into());
+    println!(
+        "  {} {} {}",
+        "Index:".bold(),
+        label(&roots),
+        if config.index.auto == Some(true) {
+            "(auto)".dimmed()
+        } else {
+            "(with -I)".dimmed()
+        }
+    );
+
+    // ── Theme ──
+    let theme_colors = [
+        ("path", &config.theme.path),
+        ("highlight", &config.theme.highlight),
+        ("accent", &config.theme.accent),
+        ("label", &config.theme.label),
+        ("warning", &config.theme.warning),
+        ("success", &config.theme.success),
+    ];
+    for (key, value) in theme_colors {
+        if let Some(value) = value
+            && parse_color(value).is_none()
+        {
+            println!();
+            println!(
+                "    {} [theme] {} = {:?} is not a known color — default used",
+                warning("WARN").bold(),
+                key,
+                value
+            );
+            warnings += 1;
+        }
+    }
+
     // ── Missing vs defaults ──
     let missing: Vec<&str> = default_targets
         .iter()
@@ -166,7 +236,7 @@ pub fn check_config(config: &LdxConfig) {
         println!();
         println!(
             "  {} flags missing vs defaults (run --sync to restore):",
-            "Missing:".yellow().bold()
+            warning("Missing:").bold()
         );
         for m in &missing {
             println!("    • {}", m);
@@ -177,21 +247,20 @@ pub fn check_config(config: &LdxConfig) {
     // ── Summary ──
     println!();
     if warnings == 0 {
-        println!("  {} Config looks great!", "OK".green().bold());
+        println!("  {} Config looks great!", success("OK").bold());
     } else {
         println!(
             "  {} {} warning{} found",
-            "!!".yellow().bold(),
+            warning("!!").bold(),
             warnings,
             if warnings == 1 { "" } else { "s" }
         );
     }
     println!(
         "{}",
-        "─────────────────────────────────────────────────"
-            .bright_cyan()
-            .bold()
+        accent("─────────────────────────────────────────────────").bold()
     );
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -234,7 +303,7 @@ pub fn sync_config() -> Result<()> {
         ));
         println!(
             "  {} added [flags.{}]  (-{} / --{})",
-            "+".green().bold(),
+            success("+").bold(),
             key,
             flag.short,
             flag.long
@@ -245,7 +314,7 @@ pub fn sync_config() -> Result<()> {
     if added == 0 {
         println!(
             "{}",
-            "Config is already up to date — nothing to sync.".green()
+            success("Config is already up to date — nothing to sync.")
         );
         return Ok(());
     }
@@ -254,7 +323,7 @@ pub fn sync_config() -> Result<()> {
     std::fs::write(&path, new_contents)?;
     println!(
         "{} {} flag{} added.",
-        "Synced:".green().bold(),
+        success("Synced:").bold(),
         added,
         if added == 1 { "" } else { "s" }
     );
@@ -269,8 +338,8 @@ pub fn reset_config() -> Result<()> {
     let path = config_path();
 
     print!(
-        "{} This will restore all [flags] to defaults.\n  Your [aliases] and [custom] flags will be preserved.\n  Continue? [y/N] ",
-        "Reset:".yellow().bold()
+        "{} This will restore all [flags] to defaults.\n  Your [aliases], [custom] flags, [theme], [open] and [index] will be preserved.\n  Continue? [y/N] ",
+        warning("Reset:").bold()
     );
     std::io::Write::flush(&mut std::io::stdout())?;
 
@@ -285,22 +354,10 @@ pub fn reset_config() -> Result<()> {
     let user: LdxConfig = toml::from_str(&user_contents)?;
     let defaults: LdxConfig = toml::from_str(DEFAULT_CONFIG)?;
 
-    let meta_block: String = {
-        let mut in_meta = false;
-        let mut lines = Vec::new();
-        for line in user_contents.lines() {
-            if line.starts_with("[meta]") {
-                in_meta = true;
-            }
-            if in_meta {
-                if line.starts_with('[') && !line.starts_with("[meta]") {
-                    break;
-                }
-                lines.push(line);
-            }
-        }
-        lines.join("\n")
-    };
+    let meta_block = extract_block(&user_contents, "[meta]");
+    let theme_block = extract_block(&user_contents, "[theme]");
+    let open_block = extract_block(&user_contents, "[open]");
+    let index_block = extract_block(&user_contents, "[index]");
 
     let mut out = String::from(
         "# localdex configuration\n\
@@ -343,6 +400,21 @@ pub fn reset_config() -> Result<()> {
         out.push('\n');
     }
 
+    if !theme_block.is_empty() {
+        out.push_str(&theme_block);
+        out.push_str("\n\n");
+    }
+
+    if !open_block.is_empty() {
+        out.push_str(&open_block);
+        out.push_str("\n\n");
+    }
+
+    if !index_block.is_empty() {
+        out.push_str(&index_block);
+        out.push_str("\n\n");
+    }
+
     if !meta_block.is_empty() {
         out.push_str(&meta_block);
         out.push('\n');
@@ -351,7 +423,7 @@ pub fn reset_config() -> Result<()> {
     std::fs::write(&path, out)?;
     println!(
         "{} Flags reset to defaults. {} alias{} and {} custom flag{} preserved.",
-        "Done:".green().bold(),
+        success("Done:").bold(),
         user.aliases.len(),
         if user.aliases.len() == 1 { "" } else { "es" },
         user.custom.len(),
@@ -359,3 +431,43 @@ pub fn reset_config() -> Result<()> {
     );
     Ok(())
 }
+
+// ---------------------------------------------------------------------------
+// Copy a whole [section] verbatim, up to the next section header
+// ---------------------------------------------------------------------------
+
+fn extract_block(contents: &str, header: &str) -> String {
+    let mut inside = false;
+    let mut lines = Vec::new();
+    for line in contents.lines() {
+        // the whole line, so `[theme]` does not also pick up `[themes]`
+        let is_header = line.trim() == header;
+        if inside && !is_header && line.trim_start().starts_with('[') {
+            break;
+        }
+        inside |= is_header;
+        if inside {
+            lines.push(line);
+        }
+    }
+    lines.join("\n").trim_end().to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_start_at_their_exact_header() {
+        let contents = "[themes]\nx = 1\n\n[theme]\npath = \"red\"\n\n[theme_extra]\ny = 2\n";
+        assert_eq!(
+            extract_block(contents, "[theme]"),
+            "[theme]\npath = \"red\""
+        );
+        assert_eq!(extract_block(contents, "[index]"), "");
+    }
+}
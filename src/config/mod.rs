@@ -28,7 +28,33 @@ pub struct FlagDef {
     pub value: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// `[theme]` — UI colors. Values are color names ("bright cyan") or `#rrggbb`.
+#[derive(Debug, Default, Deserialize)]
+pub struct ThemeConfig {
+    pub path: Option<String>,
+    pub highlight: Option<String>,
+    pub accent: Option<String>,
+    pub label: Option<String>,
+    pub warning: Option<String>,
+    pub success: Option<String>,
+    /// Color entries by type/extension from `LS_COLORS` (default: true).
+    pub ls_colors: Option<bool>,
+}
+
+/// `[index]` — what `ldx index` walks and when searches use the result.
+#[derive(Debug, Default, Deserialize)]
+pub struct IndexConfig {
+    /// Directories to index (default: `~`).
+    pub roots: Option<Vec<String>>,
+    /// Directory names never indexed, on top of `--exclude` at query time.
+    pub exclude: Option<Vec<String>>,
+    /// Answer every search under a root from the index, as if `-I` was given.
+    pub auto: Option<bool>,
+    /// Warn when the index is older than this (default: 24).
+    pub stale_after_hours: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
 pub struct LdxConfig {
     #[serde(default)]
     pub flags: HashMap<String, FlagDef>,
@@ -36,6 +62,13 @@ pub struct LdxConfig {
     pub custom: HashMap<String, FlagDef>,
     #[serde(default)]
     pub aliases: HashMap<String, String>,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// `[open]` — extension or file-name glob → opener command, `{}` = path.
+    #[serde(default)]
+    pub open: HashMap<String, String>,
+    #[serde(default)]
+    pub index: IndexConfig,
 }
 
 // ---------------------------------------------------------------------------
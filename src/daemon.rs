@@ -0,0 +1,513 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::config::{LdxConfig, config_path};
+use crate::search::{Config, ScanResult};
+
+// ---------------------------------------------------------------------------
+// ldx daemon — the index kept in memory and current through filesystem
+// events, answering queries over a Unix socket next to config.toml.
+//
+// Protocol, one request per connection:
+//   request:  "query\n" then "key\tvalue\n" lines, then an empty line
+//             (or "status\n\n" / "stop\n\n")
+//   response: "ok\tmatches\tfiles\tdirs\n" then NUL-terminated paths,
+//             "miss\n" when no root covers the directory or it lies inside
+//             a directory the daemon's excludes skip, or "err\tmsg\n"
+// ---------------------------------------------------------------------------
+
+pub fn socket_path() -> PathBuf {
+    config_path().with_file_name("ldx.sock")
+}
+
+fn encode_request(dir: &Path, config: &Config) -> String {
+    let mut out = String::from("query\n");
+    out.push_str(&format!("dir\t{}\n", dir.display()));
+    if let Some(p) = &config.pattern {
+        out.push_str(&format!("pattern\t{}\n", p));
+    }
+    if let Some(e) = &config.extension {
+        out.push_str(&format!("extension\t{}\n", e));
+    }
+    if let Some(n) = config.limit {
+        out.push_str(&format!("limit\t{}\n", n));
+    }
+    if !config.exclude.is_empty() {
+        out.push_str(&format!("exclude\t{}\n", config.exclude.join(",")));
+    }
+    for (key, on) in [
+        ("case_sensitive", config.case_sensitive),
+        ("all", config.all),
+        ("dirs_only", config.dirs_only),
+        ("collect_paths", config.collect_paths),
+    ] {
+        if on {
+            out.push_str(&format!("{}\t1\n", key));
+        }
+    }
+    out.push('\n');
+    out
+}
+
+fn decode_request(lines: &[String]) -> (PathBuf, Config) {
+    let mut dir = PathBuf::new();
+    let mut config = Config::default();
+    for line in lines {
+        let (key, value) = line.split_once('\t').unwrap_or((line, ""));
+        match key {
+            "dir" => dir = PathBuf::from(value),
+            "pattern" => config.pattern = Some(value.to_string()),
+            "extension" => config.extension = Some(value.to_string()),
+            "limit" => config.limit = value.parse().ok(),
+            "exclude" => config.exclude = value.split(',').map(String::from).collect(),
+            "case_sensitive" => config.case_sensitive = true,
+            "all" => config.all = true,
+            "dirs_only" => config.dirs_only = true,
+            "collect_paths" => config.collect_paths = true,
+            _ => {}
+        }
+    }
+    (dir, config)
+}
+
+fn encode_result(result: &ScanResult) -> Vec<u8> {
+    let mut out = format!(
+        "ok\t{}\t{}\t{}\n",
+        result.matches, result.files, result.dirs
+    )
+    .into_bytes();
+    for path in &result.paths {
+        out.extend_from_slice(&crate::index::path_bytes(path));
+        out.push(0);
+    }
+    out
+}
+
+fn decode_result(buf: &[u8]) -> Option<ScanResult> {
+    let newline = buf.iter().position(|&b| b == b'\n')?;
+    let header = std::str::from_utf8(&buf[..newline]).ok()?;
+    let mut fields = header.split('\t');
+    if fields.next()? != "ok" {
+        return None;
+    }
+    let mut count = || fields.next()?.parse::<usize>().ok();
+    let (matches, files, dirs) = (count()?, count()?, count()?);
+    let paths = buf[newline + 1..]
+        .split(|&b| b == 0)
+        .filter(|p| !p.is_empty())
+        .map(crate::index::bytes_path)
+        .collect();
+    Some(ScanResult {
+        matches,
+        files,
+        dirs,
+        duration: std::time::Duration::ZERO,
+        paths,
+        errors: Vec::new(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Client — any failure means "no daemon" and the caller scans as usual
+// ---------------------------------------------------------------------------
+
+#[cfg(unix)]
+fn request(body: &str) -> Option<Vec<u8>> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    let mut stream = UnixStream::connect(socket_path()).ok()?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .ok()?;
+    stream.write_all(body.as_bytes()).ok()?;
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).ok()?;
+    Some(buf)
+}
+
+#[cfg(not(unix))]
+fn request(_body: &str) -> Option<Vec<u8>> {
+    None
+}
+
+/// The daemon's answer for a search under `dir`, if one is running and
+/// indexes that directory.
+pub fn query(dir: &Path, config: &Config) -> Option<ScanResult> {
+    let start = std::time::Instant::now();
+    let mut result = decode_result(&request(&encode_request(dir, config))?)?;
+    result.duration = start.elapsed();
+    Some(result)
+}
+
+// ---------------------------------------------------------------------------
+// Server
+// ---------------------------------------------------------------------------
+
+#[cfg(unix)]
+mod server {
+    use anyhow::{Context, Result, bail};
+    use notify::event::ModifyKind;
+    use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+    use std::collections::BTreeMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::ops::Bound;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{self, RecvTimeoutError};
+    use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use parex::EntryKind;
+
+    use super::{decode_request, encode_result, socket_path};
+    use crate::cli::display::fmt_num;
+    use crate::config::LdxConfig;
+    use crate::index::{
+        self, Index, IndexEntry, index_path, kind_of, remove_tree, stat_entry, walk_entries,
+    };
+    use crate::search::is_excluded;
+
+    /// How often a changed index is written back to index.bin for `-I`.
+    const SAVE_EVERY: Duration = Duration::from_secs(60);
+
+    struct State {
+        entries: BTreeMap<PathBuf, IndexEntry>,
+        dirty: bool,
+    }
+
+    struct Daemon {
+        roots: Vec<PathBuf>,
+        exclude: Vec<String>,
+        threads: usize,
+        state: Mutex<State>,
+    }
+
+    impl Daemon {
+        /// A panic while the lock was held leaves the map consistent enough
+        /// to keep serving; the next event for that path repairs it.
+        fn state(&self) -> MutexGuard<'_, State> {
+            self.state.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        fn snapshot(&self) -> Index {
+            let mut state = self.state();
+            state.dirty = false;
+            Index {
+                built_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                roots: self.roots.clone(),
+                exclude: self.exclude.clone(),
+                entries: state.entries.values().cloned().collect(),
+            }
+        }
+
+        fn save(&self) {
+            let _ = self.snapshot().save(&index_path());
+        }
+
+        /// Brings one path in line with the filesystem, watching any
+        /// directory that appeared along with it.
+        fn apply(&self, watcher: &mut RecommendedWatcher, path: &Path, appeared: bool) {
+            let Some(root) = self.roots.iter().find(|r| path.starts_with(r)) else {
+                return;
+            };
+            let rel = path.strip_prefix(root).unwrap_or(path);
+            let kind = std::fs::symlink_metadata(path)
+                .ok()
+                .and_then(|m| kind_of(m.file_type()));
+            let Some(kind) = kind else {
+                let mut state = self.state();
+                state.dirty |= remove_tree(&mut state.entries, path) > 0;
+                return;
+            };
+            let is_dir = matches!(kind, EntryKind::Dir);
+            if is_excluded(rel, is_dir, &self.exclude) {
+                return;
+            }
+
+            let known = self.state().entries.get(path).map(|e| e.kind.clone());
+            let fresh = known.as_ref() != Some(&kind) || (appeared && is_dir);
+            // Walk a new directory before taking the lock. Entries created
+            // before its subdirectories are watched would never raise an
+            // event, so walk once more after the watches are in place.
+            let below = if fresh && is_dir {
+                let _ = watcher.watch(path, RecursiveMode::NonRecursive);
+                let first = walk_entries(path, &self.exclude, self.threads);
+                watch_all(
+                    watcher,
+                    first
+                        .iter()
+                        .filter(|e| matches!(e.kind, EntryKind::Dir))
+                        .map(|e| &e.path),
+                );
+                walk_entries(path, &self.exclude, self.threads)
+            } else {
+                Vec::new()
+            };
+
+            let mut state = self.state();
+            if known.is_some() && fresh {
+                remove_tree(&mut state.entries, path);
+            }
+            state
+                .entries
+                .insert(path.to_path_buf(), stat_entry(path.to_path_buf(), kind));
+            for e in below {
+                state.entries.insert(e.path.clone(), e);
+            }
+            state.dirty = true;
+        }
+
+        fn answer(&self, lines: &[String]) -> Vec<u8> {
+            let (dir, config) = decode_request(lines);
+            if !index::can_answer(&self.roots, &self.exclude, &dir) {
+                return b"miss\n".to_vec();
+            }
+            let state = self.state();
+            let below = state
+                .entries
+                .range::<Path, _>((Bound::Excluded(dir.as_path()), Bound::Unbounded))
+                .map(|(_, e)| e)
+                .take_while(|e| e.path.starts_with(&dir));
+            encode_result(&index::query_entries(below, &dir, &config))
+        }
+    }
+
+    fn serve(daemon: &Daemon, stream: UnixStream) -> Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut reader = BufReader::new(&stream);
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim_end_matches('\n').is_empty() {
+                break;
+            }
+            lines.push(line.trim_end_matches('\n').to_string());
+        }
+        let mut out = &stream;
+        match lines.first().map(String::as_str) {
+            Some("query") => out.write_all(&daemon.answer(&lines[1..]))?,
+            Some("status") => {
+                let count = daemon.state().entries.len();
+                let roots: Vec<String> = daemon
+                    .roots
+                    .iter()
+                    .map(|r| r.display().to_string())
+                    .collect();
+                out.write_all(format!("ok\t{}\t{}\n", count, roots.join("\t")).as_bytes())?;
+            }
+            Some("stop") => {
+                out.write_all(b"ok\n")?;
+                daemon.save();
+                let _ = std::fs::remove_file(socket_path());
+                std::process::exit(0);
+            }
+            _ => out.write_all(b"err\tunknown request\n")?,
+        }
+        Ok(())
+    }
+
+    fn watch_all<'a>(
+        watcher: &mut RecommendedWatcher,
+        dirs: impl IntoIterator<Item = &'a PathBuf>,
+    ) -> usize {
+        dirs.into_iter()
+            .filter(|d| watcher.watch(d, RecursiveMode::NonRecursive).is_err())
+            .count()
+    }
+
+    pub fn run(config: &LdxConfig) -> Result<()> {
+        let socket = socket_path();
+        if UnixStream::connect(&socket).is_ok() {
+            bail!("ldx daemon is already running ({}).", socket.display());
+        }
+        // left behind by a daemon that didn't shut down cleanly
+        let _ = std::fs::remove_file(&socket);
+
+        let roots = index::roots(&config.index);
+        let exclude = config.index.exclude.clone().unwrap_or_default();
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+
+        for root in &roots {
+            println!("Indexing: {}", root.display());
+        }
+        let start = std::time::Instant::now();
+        let index = match Index::load(&index_path()) {
+            Ok(index) if index.roots == roots && index.exclude == exclude => {
+                index::refresh(index, &exclude, threads).0
+            }
+            _ => index::build(&roots, &exclude, threads),
+        };
+        let _ = index.save(&index_path());
+        println!(
+            "Indexed {} entries in {:.3}s",
+            fmt_num(index.entries.len()),
+            start.elapsed().as_secs_f64()
+        );
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        let unwatched = watch_all(
+            &mut watcher,
+            index
+                .entries
+                .iter()
+                .filter(|e| matches!(e.kind, EntryKind::Dir))
+                .map(|e| &e.path),
+        );
+        if unwatched > 0 {
+            eprintln!(
+                "{} {} director{} could not be watched (inotify limit?)",
+                crate::cli::theme::warning("⚠"),
+                unwatched,
+                if unwatched == 1 { "y" } else { "ies" }
+            );
+        }
+
+        let daemon = Arc::new(Daemon {
+            roots,
+            exclude,
+            threads,
+            state: Mutex::new(State {
+                entries: index
+                    .entries
+                    .into_iter()
+                    .map(|e| (e.path.clone(), e))
+                    .collect(),
+                dirty: false,
+            }),
+        });
+
+        let listener = UnixListener::bind(&socket)
+            .with_context(|| format!("Cannot listen on {}.", socket.display()))?;
+        let server = Arc::clone(&daemon);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let daemon = Arc::clone(&server);
+                std::thread::spawn(move || {
+                    let _ = serve(&daemon, stream);
+                });
+            }
+        });
+        println!("Listening on {}", socket.display());
+
+        loop {
+            let event = match rx.recv_timeout(SAVE_EVERY) {
+                Ok(Ok(event)) => event,
+                Ok(Err(_)) => continue,
+                Err(RecvTimeoutError::Timeout) => {
+                    if daemon.state().dirty {
+                        daemon.save();
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            };
+            let appeared = matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+            );
+            for path in &event.paths {
+                daemon.apply(&mut watcher, path, appeared);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ldx daemon [--status | --stop]
+// ---------------------------------------------------------------------------
+
+pub fn run(args: &[String], config: &LdxConfig) -> Result<()> {
+    match args.first().map(String::as_str) {
+        None => serve(config),
+        Some("--status") => {
+            let reply = request("status\n\n").unwrap_or_default();
+            let reply = String::from_utf8_lossy(&reply);
+            let mut fields = reply.trim_end().split('\t');
+            if fields.next() != Some("ok") {
+                println!("ldx daemon is not running.");
+                std::process::exit(1);
+            }
+            let entries: usize = fields.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+            println!("ldx daemon: {}", socket_path().display());
+            for root in fields {
+                println!("  root: {}", root);
+            }
+            println!("  {} entries", crate::cli::display::fmt_num(entries));
+            Ok(())
+        }
+        Some("--stop") => {
+            if request("stop\n\n").is_none() {
+                println!("ldx daemon is not running.");
+                std::process::exit(1);
+            }
+            println!("ldx daemon stopped.");
+            Ok(())
+        }
+        Some(arg) => anyhow::bail!(
+            "Unknown argument: {:?}. Usage: ldx daemon [--status | --stop]",
+            arg
+        ),
+    }
+}
+
+#[cfg(unix)]
+fn serve(config: &LdxConfig) -> Result<()> {
+    server::run(config)
+}
+
+#[cfg(not(unix))]
+fn serve(_config: &LdxConfig) -> Result<()> {
+    anyhow::bail!("ldx daemon needs Unix domain sockets and is not available on this platform.")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_round_trip() {
+        let config = Config {
+            pattern: Some("invoice".into()),
+            limit: Some(3),
+            exclude: vec!["target".into(), ".git".into()],
+            dirs_only: true,
+            ..Config::default()
+        };
+        let body = encode_request(Path::new("/home/u"), &config);
+        let lines: Vec<String> = body.lines().skip(1).map(String::from).collect();
+        let (dir, decoded) = decode_request(&lines);
+        assert_eq!(dir, Path::new("/home/u"));
+        assert_eq!(decoded.pattern.as_deref(), Some("invoice"));
+        assert_eq!(decoded.limit, Some(3));
+        assert_eq!(decoded.exclude, config.exclude);
+        assert!(decoded.dirs_only && !decoded.all);
+    }
+
+    #[test]
+    fn results_round_trip() {
+        let result = ScanResult {
+            matches: 2,
+            files: 10,
+            dirs: 3,
+            duration: std::time::Duration::ZERO,
+            paths: vec!["/a/b c".into(), "/a/d".into()],
+            errors: Vec::new(),
+        };
+        let decoded = decode_result(&encode_result(&result)).unwrap();
+        assert_eq!((decoded.matches, decoded.files, decoded.dirs), (2, 10, 3));
+        assert_eq!(decoded.paths, result.paths);
+        assert!(decode_result(b"miss\n").is_none());
+    }
+}
@@ -0,0 +1,257 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+// ---------------------------------------------------------------------------
+// Command template — parsed from -x/--exec or -X/--exec-batch
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecMode {
+    /// One invocation per match, run in parallel.
+    Each,
+    /// Every match as arguments, split into as few invocations as the
+    /// argument size limit allows.
+    Batch,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandTemplate {
+    pub mode: ExecMode,
+    args: Vec<String>,
+}
+
+const PLACEHOLDERS: [&str; 5] = ["{/.}", "{//}", "{/}", "{.}", "{}"];
+
+/// Argument bytes per -X invocation. Like xargs, stay well below ARG_MAX
+/// (2 MiB on Linux, 1 MiB on macOS) so the environment still fits.
+#[cfg(not(windows))]
+const BATCH_ARG_BYTES: usize = 128 * 1024;
+/// CreateProcess caps the whole command line at 32767 characters.
+#[cfg(windows)]
+const BATCH_ARG_BYTES: usize = 30 * 1024;
+
+impl CommandTemplate {
+    /// Builds a template; a bare `{}` is appended when no argument
+    /// mentions a placeholder.
+    pub fn new(mode: ExecMode, mut args: Vec<String>) -> Self {
+        if !args[1..].iter().any(|a| has_placeholder(a)) {
+            args.push("{}".into());
+        }
+        Self { mode, args }
+    }
+
+    pub fn command_for(&self, paths: &[&Path]) -> Command {
+        let mut cmd = Command::new(&self.args[0]);
+        for arg in &self.args[1..] {
+            if has_placeholder(arg) {
+                cmd.args(paths.iter().map(|p| expand(arg, p)));
+            } else {
+                cmd.arg(arg);
+            }
+        }
+        cmd
+    }
+
+    /// Splits `paths` into runs whose expanded arguments fit in `budget`
+    /// bytes, counting each argument's terminator and argv pointer. A path
+    /// too long to share a run still gets one of its own.
+    fn batches<'a>(&self, paths: &'a [&'a Path], budget: usize) -> Vec<&'a [&'a Path]> {
+        let cost = |arg: &str| arg.len() + 1 + std::mem::size_of::<usize>();
+        let (templated, fixed): (Vec<&String>, Vec<&String>) =
+            self.args.iter().partition(|a| has_placeholder(a));
+        let fixed: usize = fixed.iter().map(|a| cost(a)).sum();
+
+        let mut runs = Vec::new();
+        let (mut start, mut used) = (0, fixed);
+        for (i, path) in paths.iter().enumerate() {
+            let size: usize = templated.iter().map(|a| cost(&expand(a, path))).sum();
+            if i > start && used + size > budget {
+                runs.push(&paths[start..i]);
+                (start, used) = (i, fixed);
+            }
+            used += size;
+        }
+        if start < paths.len() {
+            runs.push(&paths[start..]);
+        }
+        runs
+    }
+}
+
+fn has_placeholder(arg: &str) -> bool {
+    PLACEHOLDERS.iter().any(|p| arg.contains(p))
+}
+
+/// Expands every placeholder in `arg` for one path, in a single pass so
+/// braces inside the path itself are never re-expanded.
+///
+/// `{}` path, `{/}` basename, `{//}` parent, `{.}` path without extension,
+/// `{/.}` basename without extension.
+pub fn expand(arg: &str, path: &Path) -> String {
+    let lossy = |p: Option<&std::ffi::OsStr>| {
+        p.map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let mut out = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(pos) = rest.find('{') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let Some(token) = PLACEHOLDERS.iter().find(|p| rest.starts_with(*p)) else {
+            out.push('{');
+            rest = &rest[1..];
+            continue;
+        };
+        let value = match *token {
+            "{}" => path.to_string_lossy().into_owned(),
+            "{/}" => lossy(path.file_name()),
+            "{//}" => lossy(path.parent().map(Path::as_os_str)),
+            "{.}" => path.with_extension("").to_string_lossy().into_owned(),
+            _ => lossy(path.file_stem()),
+        };
+        out.push_str(&value);
+        rest = &rest[token.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Exec summary — folded into the final exit code
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+pub struct ExecSummary {
+    pub ran: usize,
+    pub failed: usize,
+    /// Highest child exit code seen; 0 when every command succeeded.
+    pub code: i32,
+}
+
+impl ExecSummary {
+    fn record(&mut self, code: i32) {
+        self.ran += 1;
+        if code != 0 {
+            self.failed += 1;
+            self.code = self.code.max(code);
+        }
+    }
+}
+
+/// Runs `cmd` and returns its exit code — 127 when it could not be started,
+/// 1 when it was killed by a signal.
+fn status_of(mut cmd: Command, program: &str, buffered: bool) -> i32 {
+    let result = if buffered {
+        cmd.stdin(Stdio::null()).output().map(|out| {
+            // hold both locks so one command's output is never interleaved
+            // with another's
+            let mut stdout = std::io::stdout().lock();
+            let mut stderr = std::io::stderr().lock();
+            let _ = stdout.write_all(&out.stdout);
+            let _ = stderr.write_all(&out.stderr);
+            out.status
+        })
+    } else {
+        cmd.status()
+    };
+    match result {
+        Ok(status) => status.code().unwrap_or(1),
+        Err(e) => {
+            eprintln!("Failed to run {:?}: {}", program, e);
+            127
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// run — per match on `jobs` workers, or batched under the argument limit
+// ---------------------------------------------------------------------------
+
+pub fn run(template: &CommandTemplate, paths: &[std::path::PathBuf], jobs: usize) -> ExecSummary {
+    let program = template.args[0].as_str();
+
+    if template.mode == ExecMode::Batch {
+        let mut summary = ExecSummary::default();
+        let refs: Vec<&Path> = paths.iter().map(|p| p.as_path()).collect();
+        for run in template.batches(&refs, BATCH_ARG_BYTES) {
+            summary.record(status_of(template.command_for(run), program, false));
+        }
+        return summary;
+    }
+
+    // a single worker can hand the terminal straight to the child
+    let buffered = jobs > 1;
+    let next = AtomicUsize::new(0);
+    let summary = Mutex::new(ExecSummary::default());
+
+    std::thread::scope(|s| {
+        for _ in 0..jobs.clamp(1, paths.len().max(1)) {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(i) else { break };
+                    let code = status_of(template.command_for(&[path]), program, buffered);
+                    summary
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .record(code);
+                }
+            });
+        }
+    });
+
+    summary.into_inner().unwrap_or_else(PoisonError::into_inner)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_every_placeholder() {
+        let path = Path::new("/music/album/track.flac");
+        assert_eq!(expand("{}", path), "/music/album/track.flac");
+        assert_eq!(expand("{/}", path), "track.flac");
+        assert_eq!(expand("{//}", path), "/music/album");
+        assert_eq!(expand("{.}.mp3", path), "/music/album/track.mp3");
+        assert_eq!(expand("out/{/.}.mp3", path), "out/track.mp3");
+        assert_eq!(expand("{x}", path), "{x}");
+    }
+
+    #[test]
+    fn braces_in_paths_are_not_re_expanded() {
+        assert_eq!(expand("{/}", Path::new("/tmp/{}.txt")), "{}.txt");
+    }
+
+    #[test]
+    fn bare_command_gets_trailing_path() {
+        let t = CommandTemplate::new(ExecMode::Each, vec!["chmod".into(), "+x".into()]);
+        assert_eq!(t.args, ["chmod", "+x", "{}"]);
+        let t = CommandTemplate::new(
+            ExecMode::Each,
+            vec!["mv".into(), "{}".into(), "/tmp".into()],
+        );
+        assert_eq!(t.args, ["mv", "{}", "/tmp"]);
+    }
+
+    #[test]
+    fn batches_split_by_argument_bytes() {
+        let t = CommandTemplate::new(ExecMode::Batch, vec!["rm".into()]);
+        let paths = ["/a/1", "/a/2", "/a/3", "/a/very/long/path/4"].map(Path::new);
+        // "rm" costs 11 bytes and each short path 13 on 64-bit targets
+        let per = |arg: &str| arg.len() + 1 + std::mem::size_of::<usize>();
+        let budget = per("rm") + 2 * per("/a/1");
+
+        let runs = t.batches(&paths, budget);
+        assert_eq!(runs.iter().map(|r| r.len()).collect::<Vec<_>>(), [2, 1, 1]);
+        assert_eq!(t.batches(&paths, usize::MAX).len(), 1);
+        assert!(t.batches(&[], budget).is_empty());
+    }
+}
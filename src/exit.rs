@@ -0,0 +1,93 @@
+use std::process::ExitCode;
+
+use crate::cancel::Stop;
+
+// ---------------------------------------------------------------------------
+// Exit codes — documented in the README; scripts depend on them
+// ---------------------------------------------------------------------------
+
+/// At least one match, and the walk read everything.
+pub const MATCHES: u8 = 0;
+pub const NO_MATCHES: u8 = 1;
+/// Bad flags or arguments.
+pub const USAGE: u8 = 2;
+/// `config.toml` is missing or invalid, or could not be written.
+pub const CONFIG: u8 = 3;
+/// The search finished but skipped paths it could not read, so the results
+/// may be incomplete. Takes precedence over 0 and 1.
+pub const PARTIAL: u8 = 4;
+/// Anything else, e.g. a file that could not be opened or moved.
+pub const FAILURE: u8 = 5;
+/// Ctrl-C, the shell convention of 128 + SIGINT.
+pub const INTERRUPTED: u8 = 130;
+
+/// The code for a finished search; a timeout counts as partial results.
+pub fn for_search(matches: usize, errors: usize, stop: Option<Stop>) -> ExitCode {
+    ExitCode::from(if stop == Some(Stop::Interrupted) {
+        INTERRUPTED
+    } else if errors > 0 || stop.is_some() {
+        PARTIAL
+    } else if matches == 0 {
+        NO_MATCHES
+    } else {
+        MATCHES
+    })
+}
+
+/// An error together with the code to exit with; `?` on other errors means
+/// [`FAILURE`].
+pub struct Failure {
+    pub code: u8,
+    pub error: anyhow::Error,
+}
+
+impl From<anyhow::Error> for Failure {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            code: FAILURE,
+            error,
+        }
+    }
+}
+
+impl From<std::io::Error> for Failure {
+    fn from(error: std::io::Error) -> Self {
+        anyhow::Error::from(error).into()
+    }
+}
+
+pub fn usage(error: anyhow::Error) -> Failure {
+    Failure { code: USAGE, error }
+}
+
+pub fn config(error: anyhow::Error) -> Failure {
+    Failure {
+        code: CONFIG,
+        error,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skipped_paths_and_stops_outrank_match_count() {
+        assert_eq!(for_search(3, 0, None), ExitCode::from(MATCHES));
+        assert_eq!(for_search(0, 0, None), ExitCode::from(NO_MATCHES));
+        assert_eq!(for_search(0, 2, None), ExitCode::from(PARTIAL));
+        assert_eq!(for_search(3, 2, None), ExitCode::from(PARTIAL));
+        assert_eq!(
+            for_search(3, 0, Some(Stop::TimedOut)),
+            ExitCode::from(PARTIAL)
+        );
+        assert_eq!(
+            for_search(3, 2, Some(Stop::Interrupted)),
+            ExitCode::from(INTERRUPTED)
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use anyhow::{Result, bail};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cli::launcher::choose;
+use crate::config::config_path;
+use crate::search::{Config, scan_dir};
+
+// ---------------------------------------------------------------------------
+// Database — one `rank<TAB>last-access<TAB>path` line per directory, kept
+// next to config.toml
+// ---------------------------------------------------------------------------
+
+/// Once the ranks add up to this, every entry decays so old favourites fade.
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+
+pub struct Dir {
+    pub path: PathBuf,
+    pub rank: f64,
+    /// Seconds since the Unix epoch.
+    pub last: u64,
+}
+
+#[derive(Default)]
+pub struct Db {
+    pub dirs: Vec<Dir>,
+}
+
+pub fn db_path() -> PathBuf {
+    config_path().with_file_name("frecency.tsv")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Frequency weighted by recency: the last hour counts 4×, the last day 2×,
+/// the last week ½×, anything older ¼×.
+pub fn frecency(rank: f64, last: u64, now: u64) -> f64 {
+    let weight = match now.saturating_sub(last) {
+        age if age < 3_600 => 4.0,
+        age if age < 86_400 => 2.0,
+        age if age < 604_800 => 0.5,
+        _ => 0.25,
+    };
+    rank * weight
+}
+
+impl Db {
+    /// Unreadable files and malformed lines are skipped — this is a cache.
+    pub fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(db_path()) else {
+            return Db::default();
+        };
+        let dirs = contents
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                Some(Dir {
+                    rank: parts.next()?.parse().ok()?,
+                    last: parts.next()?.parse().ok()?,
+                    path: PathBuf::from(parts.next()?),
+                })
+            })
+            .collect();
+        Db { dirs }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = db_path();
+        let mut out = String::new();
+        for d in &self.dirs {
+            out.push_str(&format!("{}\t{}\t{}\n", d.rank, d.last, d.path.display()));
+        }
+        // write-then-rename so a concurrent reader never sees half a file
+        let tmp = path.with_extension("tsv.tmp");
+        std::fs::write(&tmp, out)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn record(&mut self, dir: &Path, now: u64) {
+        match self.dirs.iter_mut().find(|d| d.path == dir) {
+            Some(d) => {
+                d.rank += 1.0;
+                d.last = now;
+            }
+            None => self.dirs.push(Dir {
+                path: dir.to_path_buf(),
+                rank: 1.0,
+                last: now,
+            }),
+        }
+        let total: f64 = self.dirs.iter().map(|d| d.rank).sum();
+        if total > MAX_TOTAL_RANK {
+            for d in &mut self.dirs {
+                d.rank *= 0.9;
+            }
+            self.dirs.retain(|d| d.rank >= 1.0);
+        }
+    }
+
+    /// Known directories matching `terms`, best first.
+    pub fn ranked(&self, terms: &[String], now: u64) -> Vec<&Dir> {
+        let mut hits: Vec<&Dir> = self
+            .dirs
+            .iter()
+            .filter(|d| matches_terms(&d.path, terms))
+            .collect();
+        hits.sort_by(|a, b| {
+            frecency(b.rank, b.last, now).total_cmp(&frecency(a.rank, a.last, now))
+        });
+        hits
+    }
+}
+
+/// zoxide-style: terms appear in order, case-insensitively, and the last one
+/// is in the final path component.
+pub fn matches_terms(path: &Path, terms: &[String]) -> bool {
+    let full = path.to_string_lossy().to_lowercase();
+    let mut from = 0;
+    for term in terms {
+        let term = term.to_lowercase();
+        match full[from..].find(&term) {
+            Some(i) => from += i + term.len(),
+            None => return false,
+        }
+    }
+    match (terms.last(), path.file_name()) {
+        (Some(last), Some(name)) => name
+            .to_string_lossy()
+            .to_lowercase()
+            .contains(&last.to_lowercase()),
+        (None, _) => true,
+        (Some(_), None) => false,
+    }
+}
+
+/// Bumps each directory once; failures only cost ranking, never the command.
+pub fn remember<'a>(dirs: impl IntoIterator<Item = &'a Path>) {
+    let mut db = Db::load();
+    let now = now();
+    let mut seen: Vec<&Path> = Vec::new();
+    for dir in dirs {
+        if !seen.contains(&dir) {
+            seen.push(dir);
+            db.record(dir, now);
+        }
+    }
+    let _ = db.save();
+}
+
+// ---------------------------------------------------------------------------
+// ldx jump QUERY — known directories first, then a live walk of $HOME
+// ---------------------------------------------------------------------------
+
+fn discover(terms: &[String], threads: usize) -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let config = Config {
+        dirs_only: true,
+        pattern: terms.last().cloned(),
+        threads,
+        collect_paths: true,
+        ..Config::default()
+    };
+    let mut hits: Vec<PathBuf> = scan_dir(&home, &config)
+        .map(|result| result.paths)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| matches_terms(p, terms))
+        .collect();
+    // shallowest first — `ldx jump src` wants ~/src, not ~/a/b/c/src
+    hits.sort_by_key(|p| (p.components().count(), p.clone()));
+    hits
+}
+
+pub fn run(args: &[String], cd_file: Option<&Path>) -> Result<()> {
+    let list = args.iter().any(|a| a == "-l" || a == "--list");
+    let terms: Vec<String> = args
+        .iter()
+        .filter(|a| !a.starts_with('-'))
+        .cloned()
+        .collect();
+    let db = Db::load();
+    let now = now();
+
+    if list {
+        for d in db.ranked(&terms, now) {
+            println!(
+                "{:>8.1}  {}",
+                frecency(d.rank, d.last, now),
+                d.path.display()
+            );
+        }
+        return Ok(());
+    }
+    if terms.is_empty() {
+        bail!("Usage: ldx jump QUERY...   (ldx jump --list to see ranked directories)");
+    }
+
+    let known = db
+        .ranked(&terms, now)
+        .into_iter()
+        .map(|d| d.path.clone())
+        .find(|p| p.is_dir());
+
+    let target = match known {
+        Some(dir) => dir,
+        None => {
+            let threads = std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4);
+            let found = discover(&terms, threads);
+            let picked = match found.len() {
+                0 => None,
+                1 => found.into_iter().next(),
+                _ => {
+                    let labels = found.iter().map(|p| p.display().to_string()).collect();
+                    choose(&found, labels)?.into_iter().next()
+                }
+            };
+            match picked {
+                Some(dir) => dir,
+                None => bail!("No directory matches {:?}.", terms.join(" ")),
+            }
+        }
+    };
+
+    remember([target.as_path()]);
+    match cd_file {
+        Some(file) => std::fs::write(file, target.to_string_lossy().as_bytes())?,
+        None => println!("{}", target.display()),
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn terms_match_in_order_with_last_in_basename() {
+        let path = Path::new("/home/u/code/LocalDex/src");
+        assert!(matches_terms(path, &terms("code src")));
+        assert!(matches_terms(path, &terms("localdex s")));
+        assert!(!matches_terms(path, &terms("src code")));
+        assert!(!matches_terms(path, &terms("localdex")));
+    }
+
+    #[test]
+    fn recent_visits_outrank_old_frequent_ones() {
+        let now = 10_000_000;
+        let mut db = Db::default();
+        for _ in 0..5 {
+            db.record(Path::new("/old/proj"), now - 30 * 86_400);
+        }
+        for _ in 0..2 {
+            db.record(Path::new("/new/proj"), now - 60);
+        }
+        let ranked = db.ranked(&terms("proj"), now);
+        assert_eq!(ranked[0].path, Path::new("/new/proj"));
+        assert_eq!(ranked[1].rank, 5.0);
+    }
+
+    #[test]
+    fn ranks_decay_past_the_cap() {
+        let mut db = Db::default();
+        db.dirs.push(Dir {
+            path: "/a".into(),
+            rank: MAX_TOTAL_RANK,
+            last: 0,
+        });
+        db.dirs.push(Dir {
+            path: "/b".into(),
+            rank: 1.0,
+            last: 0,
+        });
+        db.record(Path::new("/c"), 0);
+        assert_eq!(db.dirs.len(), 1);
+        assert!(db.dirs[0].rank < MAX_TOTAL_RANK);
+    }
+}
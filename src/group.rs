@@ -0,0 +1,183 @@
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::path::{Component, Path};
+use std::str::FromStr;
+
+use parex::{Entry, EntryKind};
+
+use crate::search::{Config, ScanResult, matcher_for, source_for};
+
+// ---------------------------------------------------------------------------
+// Group key — parsed from --group-by ext|dir|depth
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKey {
+    Ext,
+    Dir,
+    Depth,
+}
+
+impl FromStr for GroupKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "ext" => Ok(GroupKey::Ext),
+            "dir" => Ok(GroupKey::Dir),
+            "depth" => Ok(GroupKey::Depth),
+            _ => bail!(
+                "Invalid --group-by value: {:?}. Expected ext, dir or depth.",
+                s
+            ),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Group result
+// ---------------------------------------------------------------------------
+
+pub struct Group {
+    pub key: String,
+    pub count: usize,
+    pub bytes: u64,
+}
+
+pub struct GroupResult {
+    /// Buckets, largest first (by bytes when sizes were collected, else count).
+    pub groups: Vec<Group>,
+    pub scan: ScanResult,
+}
+
+fn bucket(key: GroupKey, root: &Path, entry: &Entry) -> String {
+    match key {
+        GroupKey::Ext => entry
+            .path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| "(none)".into()),
+        GroupKey::Dir => {
+            let rel = entry.path.strip_prefix(root).unwrap_or(&entry.path);
+            let mut comps = rel.components();
+            match (comps.next(), comps.next()) {
+                (Some(Component::Normal(top)), Some(_)) => top.to_string_lossy().into_owned(),
+                _ => ".".into(),
+            }
+        }
+        GroupKey::Depth => entry.depth.to_string(),
+    }
+}
+
+/// A file's size; directories count 0 because their inode size says nothing
+/// about their contents, matching how `--top` leaves them out.
+fn file_bytes(entry: &Entry) -> u64 {
+    if !matches!(entry.kind, EntryKind::File) {
+        return 0;
+    }
+    std::fs::symlink_metadata(&entry.path)
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+// ---------------------------------------------------------------------------
+// scan_groups — one tally per walker thread, merged at the end
+// ---------------------------------------------------------------------------
+
+pub fn scan_groups(dir: &Path, config: &Config, key: GroupKey, sizes: bool) -> GroupResult {
+    let root = dir.to_path_buf();
+
+    let folded = source_for(dir, config).fold(
+        config.threads,
+        matcher_for(config),
+        HashMap::<String, (usize, u64)>::new,
+        move |tally, entry| {
+            let bytes = if sizes { file_bytes(&entry) } else { 0 };
+            let slot = tally.entry(bucket(key, &root, &entry)).or_default();
+            slot.0 += 1;
+            slot.1 += bytes;
+        },
+    );
+
+    let mut merged: HashMap<String, (usize, u64)> = HashMap::new();
+    for tally in folded.parts {
+        for (k, (count, bytes)) in tally {
+            let slot = merged.entry(k).or_default();
+            slot.0 += count;
+            slot.1 += bytes;
+        }
+    }
+
+    let mut groups: Vec<Group> = merged
+        .into_iter()
+        .map(|(key, (count, bytes))| Group { key, count, bytes })
+        .collect();
+    if sizes {
+        groups.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.key.cmp(&b.key)));
+    } else {
+        groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    }
+
+    GroupResult {
+        scan: ScanResult {
+            matches: groups.iter().map(|g| g.count).sum(),
+            files: folded.files,
+            dirs: folded.dirs,
+            duration: folded.duration,
+            paths: Vec::new(),
+            errors: folded.errors,
+        },
+        groups,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, depth: usize) -> Entry {
+        Entry {
+            path: path.into(),
+            kind: EntryKind::File,
+            depth,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn ext_bucket_is_lowercased_with_fallback() {
+        let root = Path::new("/r");
+        assert_eq!(bucket(GroupKey::Ext, root, &entry("/r/A.PDF", 1)), "pdf");
+        assert_eq!(
+            bucket(GroupKey::Ext, root, &entry("/r/Makefile", 1)),
+            "(none)"
+        );
+    }
+
+    #[test]
+    fn only_files_add_bytes() {
+        let dir = crate::tempdir::TempDir::new("group");
+        std::fs::write(dir.join("f"), "12345").unwrap();
+        let file = entry(&dir.join("f").to_string_lossy(), 1);
+        let folder = Entry {
+            kind: EntryKind::Dir,
+            ..entry(&dir.to_string_lossy(), 0)
+        };
+        assert_eq!(file_bytes(&file), 5);
+        assert_eq!(file_bytes(&folder), 0);
+    }
+
+    #[test]
+    fn dir_bucket_uses_top_level_directory() {
+        let root = Path::new("/r");
+        assert_eq!(
+            bucket(GroupKey::Dir, root, &entry("/r/src/cli/x.rs", 3)),
+            "src"
+        );
+        assert_eq!(bucket(GroupKey::Dir, root, &entry("/r/README.md", 1)), ".");
+    }
+}
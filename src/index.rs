@@ -0,0 +1,837 @@
+use anyhow::{Context, Result, bail};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parex::{Entry, EntryKind, Matcher};
+
+use crate::cli::display::fmt_num;
+use crate::cli::flags::ParsedFlags;
+use crate::cli::theme::warning;
+use crate::config::{IndexConfig, LdxConfig, config_path};
+use crate::search::{Config, ScanResult, is_excluded, matcher_for};
+use crate::source::DirectorySource;
+
+// ---------------------------------------------------------------------------
+// On-disk format — locate-style front coding, everything else varints:
+//
+//   "LDXI" version:u8 built_at roots:[len bytes] exclude:[len bytes] count
+//   per entry, in path order: kind:u8 shared suffix_len suffix size mtime
+//
+// `shared` is how many leading path bytes repeat from the previous entry,
+// so a sibling costs little more than its own name.
+// ---------------------------------------------------------------------------
+
+const MAGIC: &[u8; 4] = b"LDXI";
+const VERSION: u8 = 3;
+
+#[derive(Clone)]
+pub struct IndexEntry {
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    pub size: u64,
+    /// Seconds since the Unix epoch.
+    pub mtime: u64,
+}
+
+pub struct Index {
+    /// Seconds since the Unix epoch.
+    pub built_at: u64,
+    pub roots: Vec<PathBuf>,
+    /// `[index] exclude` when it was built; those directories are missing.
+    pub exclude: Vec<String>,
+    /// Sorted by `Path` order, so every subtree is one contiguous run.
+    pub entries: Vec<IndexEntry>,
+}
+
+pub fn index_path() -> PathBuf {
+    config_path().with_file_name("index.bin")
+}
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(unix)]
+pub fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+pub fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(unix)]
+pub fn bytes_path(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+pub fn bytes_path(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+fn put_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8> {
+        let b = *self.buf.get(self.pos).context("Index file is truncated.")?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            n |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        bail!("Index file is corrupt.")
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&[u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .context("Index file is corrupt.")?;
+        let out = self
+            .buf
+            .get(self.pos..end)
+            .context("Index file is truncated.")?;
+        self.pos = end;
+        Ok(out)
+    }
+}
+
+fn kind_byte(kind: &EntryKind) -> u8 {
+    match kind {
+        EntryKind::File => 0,
+        EntryKind::Dir => 1,
+        EntryKind::Symlink => 2,
+        EntryKind::Other => 3,
+    }
+}
+
+fn byte_kind(b: u8) -> Result<EntryKind> {
+    match b {
+        0 => Ok(EntryKind::File),
+        1 => Ok(EntryKind::Dir),
+        2 => Ok(EntryKind::Symlink),
+        3 => Ok(EntryKind::Other),
+        _ => bail!("Index file is corrupt."),
+    }
+}
+
+impl Index {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.entries.len() * 24);
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        put_varint(&mut out, self.built_at);
+        put_varint(&mut out, self.roots.len() as u64);
+        for root in &self.roots {
+            let bytes = path_bytes(root);
+            put_varint(&mut out, bytes.len() as u64);
+            out.extend_from_slice(&bytes);
+        }
+        put_varint(&mut out, self.exclude.len() as u64);
+        for name in &self.exclude {
+            put_varint(&mut out, name.len() as u64);
+            out.extend_from_slice(name.as_bytes());
+        }
+        put_varint(&mut out, self.entries.len() as u64);
+
+        let mut prev: Vec<u8> = Vec::new();
+        for e in &self.entries {
+            let bytes = path_bytes(&e.path);
+            let shared = prev.iter().zip(&bytes).take_while(|(a, b)| a == b).count();
+            out.push(kind_byte(&e.kind));
+            put_varint(&mut out, shared as u64);
+            put_varint(&mut out, (bytes.len() - shared) as u64);
+            out.extend_from_slice(&bytes[shared..]);
+            put_varint(&mut out, e.size);
+            put_varint(&mut out, e.mtime);
+            prev = bytes;
+        }
+        out
+    }
+
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        if buf.len() < 5 || &buf[..4] != MAGIC {
+            bail!("Not an ldx index file.");
+        }
+        if buf[4] != VERSION {
+            bail!("Index was written by another ldx version; run `ldx index` again.");
+        }
+        let mut r = Reader { buf, pos: 5 };
+        let built_at = r.varint()?;
+        let root_count = r.varint()? as usize;
+        let mut roots = Vec::with_capacity(root_count.min(64));
+        for _ in 0..root_count {
+            let len = r.varint()? as usize;
+            roots.push(bytes_path(r.bytes(len)?));
+        }
+        let exclude_count = r.varint()? as usize;
+        let mut exclude = Vec::with_capacity(exclude_count.min(64));
+        for _ in 0..exclude_count {
+            let len = r.varint()? as usize;
+            exclude.push(String::from_utf8_lossy(r.bytes(len)?).into_owned());
+        }
+
+        let count = r.varint()? as usize;
+        let mut entries = Vec::with_capacity(count.min(buf.len()));
+        let mut prev: Vec<u8> = Vec::new();
+        for _ in 0..count {
+            let kind = byte_kind(r.byte()?)?;
+            let shared = r.varint()? as usize;
+            let suffix_len = r.varint()? as usize;
+            if shared > prev.len() {
+                bail!("Index file is corrupt.");
+            }
+            prev.truncate(shared);
+            prev.extend_from_slice(r.bytes(suffix_len)?);
+            entries.push(IndexEntry {
+                path: bytes_path(&prev),
+                kind,
+                size: r.varint()?,
+                mtime: r.varint()?,
+            });
+        }
+        Ok(Index {
+            built_at,
+            roots,
+            exclude,
+            entries,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let buf = std::fs::read(path)
+            .with_context(|| format!("No index at {}. Run `ldx index` first.", path.display()))?;
+        Index::decode(&buf)
+    }
+
+    /// Write-then-rename so a reader never sees half an index.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("bin.tmp");
+        std::fs::write(&tmp, self.encode())?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn covers(&self, dir: &Path) -> bool {
+        self.roots.iter().any(|root| dir.starts_with(root))
+    }
+
+    /// Whether this index can answer a search of `dir`.
+    pub fn answers(&self, dir: &Path) -> bool {
+        self.covers(dir) && skipped_in(&self.roots, &self.exclude, dir).is_none()
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_secs(SystemTime::now()).saturating_sub(self.built_at))
+    }
+
+    /// Entries strictly below `dir` — one contiguous run thanks to path order.
+    fn subtree(&self, dir: &Path) -> &[IndexEntry] {
+        let start = self.entries.partition_point(|e| e.path.as_path() <= dir);
+        let len = self.entries[start..]
+            .iter()
+            .take_while(|e| e.path.starts_with(dir))
+            .count();
+        &self.entries[start..start + len]
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Build — one DirectorySource fold per root, metadata read on walker threads
+// ---------------------------------------------------------------------------
+
+/// Indexes every entry, not just what a search would match.
+struct Everything;
+
+impl Matcher for Everything {
+    fn is_match(&self, _entry: &Entry) -> bool {
+        true
+    }
+}
+
+pub fn stat_entry(path: PathBuf, kind: EntryKind) -> IndexEntry {
+    let meta = std::fs::symlink_metadata(&path).ok();
+    IndexEntry {
+        size: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+        mtime: meta
+            .and_then(|m| m.modified().ok())
+            .map(unix_secs)
+            .unwrap_or(0),
+        path,
+        kind,
+    }
+}
+
+/// Everything below `root`, not including `root` itself.
+pub fn walk_entries(root: &Path, exclude: &[String], threads: usize) -> Vec<IndexEntry> {
+    let folded = DirectorySource::new(root)
+        .exclude(exclude.to_vec())
+        .follow_links(false)
+        .fold(
+            threads,
+            Arc::new(Everything),
+            Vec::new,
+            |acc: &mut Vec<IndexEntry>, entry| acc.push(stat_entry(entry.path, entry.kind)),
+        );
+    folded.parts.into_iter().flatten().collect()
+}
+
+/// Roots are stored as entries too, so a refresh can compare their mtimes.
+pub fn build(roots: &[PathBuf], exclude: &[String], threads: usize) -> Index {
+    let mut entries: Vec<IndexEntry> = roots
+        .iter()
+        .flat_map(|root| {
+            let mut walked = walk_entries(root, exclude, threads);
+            walked.push(stat_entry(root.clone(), EntryKind::Dir));
+            walked
+        })
+        .collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries.dedup_by(|a, b| a.path == b.path);
+    Index {
+        built_at: unix_secs(SystemTime::now()),
+        roots: roots.to_vec(),
+        exclude: exclude.to_vec(),
+        entries,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Refresh — adding, removing or renaming an entry bumps its parent's mtime,
+// so only directories whose mtime moved are read again. Edits inside a file
+// don't, which is why sizes and file mtimes can lag until a full rebuild.
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+pub struct RefreshStats {
+    pub rescanned: usize,
+    pub skipped: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+pub fn kind_of(file_type: std::fs::FileType) -> Option<EntryKind> {
+    if file_type.is_dir() {
+        Some(EntryKind::Dir)
+    } else if file_type.is_symlink() {
+        Some(EntryKind::Symlink)
+    } else if file_type.is_file() {
+        Some(EntryKind::File)
+    } else {
+        None
+    }
+}
+
+/// Drops `path` and, for a directory, everything below it.
+pub fn remove_tree(entries: &mut BTreeMap<PathBuf, IndexEntry>, path: &Path) -> usize {
+    let doomed: Vec<PathBuf> = entries
+        .range(path.to_path_buf()..)
+        .map(|(p, _)| p)
+        .take_while(|p| p.starts_with(path))
+        .cloned()
+        .collect();
+    for p in &doomed {
+        entries.remove(p);
+    }
+    doomed.len()
+}
+
+pub fn refresh(old: Index, exclude: &[String], threads: usize) -> (Index, RefreshStats) {
+    let mut stats = RefreshStats::default();
+    let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    for e in &old.entries {
+        if let Some(parent) = e.path.parent() {
+            children
+                .entry(parent.to_path_buf())
+                .or_default()
+                .push(e.path.clone());
+        }
+    }
+    let dirs: Vec<PathBuf> = old
+        .entries
+        .iter()
+        .filter(|e| matches!(e.kind, EntryKind::Dir))
+        .map(|e| e.path.clone())
+        .collect();
+    let mut entries: BTreeMap<PathBuf, IndexEntry> = old
+        .entries
+        .into_iter()
+        .map(|e| (e.path.clone(), e))
+        .collect();
+
+    for dir in dirs {
+        // already dropped along with a removed parent
+        let Some(known) = entries.get(&dir).map(|e| e.mtime) else {
+            continue;
+        };
+        let current = stat_entry(dir.clone(), EntryKind::Dir);
+        let is_dir = std::fs::symlink_metadata(&dir).is_ok_and(|m| m.is_dir());
+        if !is_dir {
+            stats.removed += remove_tree(&mut entries, &dir);
+            continue;
+        }
+        // a change in the same second as the last build may not have moved
+        // the mtime yet, so that second never counts as unchanged
+        if current.mtime == known && known < old.built_at {
+            stats.skipped += 1;
+            continue;
+        }
+        stats.rescanned += 1;
+
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        for child in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let Some(kind) = child.file_type().ok().and_then(kind_of) else {
+                continue;
+            };
+            let name = child.file_name();
+            if matches!(kind, EntryKind::Dir) && exclude.iter().any(|x| name == x.as_str()) {
+                continue;
+            }
+            let path = child.path();
+            seen.insert(path.clone());
+            match entries.get(&path) {
+                Some(e) if e.kind == kind => continue,
+                Some(_) => stats.removed += remove_tree(&mut entries, &path),
+                None => {}
+            }
+            if matches!(kind, EntryKind::Dir) {
+                for e in walk_entries(&path, exclude, threads) {
+                    stats.added += 1;
+                    entries.insert(e.path.clone(), e);
+                }
+            }
+            stats.added += 1;
+            entries.insert(path.clone(), stat_entry(path, kind));
+        }
+        for gone in children.get(&dir).into_iter().flatten() {
+            if !seen.contains(gone) {
+                stats.removed += remove_tree(&mut entries, gone);
+            }
+        }
+        entries.insert(dir, current);
+    }
+
+    let index = Index {
+        built_at: unix_secs(SystemTime::now()),
+        roots: old.roots,
+        exclude: exclude.to_vec(),
+        entries: entries.into_values().collect(),
+    };
+    (index, stats)
+}
+
+// ---------------------------------------------------------------------------
+// Query — the same matcher and exclude rules as a live scan
+// ---------------------------------------------------------------------------
+
+/// Whether an index of `roots` built without the `skipped` directories can
+/// answer a search of `dir`. Indexed searches leave those directories out as
+/// if they were passed to `--exclude`, so `dir` only has to be under a root
+/// and not inside one of them.
+pub fn can_answer(roots: &[PathBuf], skipped: &[String], dir: &Path) -> bool {
+    roots.iter().any(|r| dir.starts_with(r)) && skipped_in(roots, skipped, dir).is_none()
+}
+
+/// The skipped directory name on the way from `dir`'s root down to `dir`.
+fn skipped_in<'a>(roots: &[PathBuf], skipped: &'a [String], dir: &Path) -> Option<&'a str> {
+    let root = roots.iter().find(|r| dir.starts_with(r))?;
+    let rel = dir.strip_prefix(root).unwrap_or(dir);
+    rel.components().find_map(|c| {
+        skipped
+            .iter()
+            .find(|s| c.as_os_str() == s.as_str())
+            .map(String::as_str)
+    })
+}
+
+pub fn query(index: &Index, dir: &Path, config: &Config) -> ScanResult {
+    query_entries(index.subtree(dir), dir, config)
+}
+
+/// `subtree` must be the entries strictly below `dir`, in path order.
+pub fn query_entries<'a>(
+    subtree: impl IntoIterator<Item = &'a IndexEntry>,
+    dir: &Path,
+    config: &Config,
+) -> ScanResult {
+    let start = Instant::now();
+    let matcher = matcher_for(config);
+    let mut result = ScanResult {
+        matches: 0,
+        files: 0,
+        dirs: 0,
+        duration: Duration::ZERO,
+        paths: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for e in subtree {
+        let rel = e.path.strip_prefix(dir).unwrap_or(&e.path);
+        let is_dir = matches!(e.kind, EntryKind::Dir);
+        if is_excluded(rel, is_dir, &config.exclude)
+            || (config.dirs_only && matches!(e.kind, EntryKind::File))
+        {
+            continue;
+        }
+        match e.kind {
+            EntryKind::Dir => result.dirs += 1,
+            EntryKind::File => result.files += 1,
+            _ => {}
+        }
+        let entry = Entry {
+            path: e.path.clone(),
+            kind: e.kind.clone(),
+            depth: rel.components().count(),
+            metadata: None,
+        };
+        if !matcher.is_match(&entry) {
+            continue;
+        }
+        result.matches += 1;
+        if config.collect_paths {
+            result.paths.push(entry.path);
+        }
+        if config.limit.is_some_and(|lim| result.matches >= lim) {
+            break;
+        }
+    }
+    result.duration = start.elapsed();
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Roots and staleness from [index]
+// ---------------------------------------------------------------------------
+
+pub fn roots(config: &IndexConfig) -> Vec<PathBuf> {
+    match &config.roots {
+        Some(roots) if !roots.is_empty() => roots
+            .iter()
+            .map(|r| crate::resolve_dir(PathBuf::from(r)))
+            .collect(),
+        _ => dirs::home_dir().into_iter().collect(),
+    }
+}
+
+pub fn is_stale(index: &Index, config: &IndexConfig) -> bool {
+    index.age() > Duration::from_secs(config.stale_after_hours.unwrap_or(24) * 3600)
+}
+
+/// "just now", "5m", "3h", "2d" — for the staleness note.
+pub fn fmt_age(age: Duration) -> String {
+    match age.as_secs() {
+        s if s < 60 => "just now".into(),
+        s if s < 3_600 => format!("{}m ago", s / 60),
+        s if s < 86_400 => format!("{}h ago", s / 3_600),
+        s => format!("{}d ago", s / 86_400),
+    }
+}
+
+/// The index a search under `dir` should be answered from. `-I` insists on
+/// one; `[index] auto` quietly falls back to a live scan when there is none.
+pub fn for_search(dir: &Path, f: &ParsedFlags, config: &IndexConfig) -> Result<Option<Index>> {
+    if f.live || !(f.index || config.auto == Some(true)) {
+        return Ok(None);
+    }
+    let index = match Index::load(&index_path()) {
+        Ok(index) => index,
+        Err(_) if !f.index => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    usable(index, dir, f)
+}
+
+/// `index` if it can answer a search of `dir`; `-I` turns a miss into an
+/// error that says why.
+fn usable(index: Index, dir: &Path, f: &ParsedFlags) -> Result<Option<Index>> {
+    if index.answers(dir) {
+        return Ok(Some(index));
+    }
+    if f.index
+        && let Some(name) = skipped_in(&index.roots, &index.exclude, dir)
+    {
+        bail!(
+            "{} is inside {}, which the index leaves out ([index] exclude). Use --live to search it.",
+            dir.display(),
+            name
+        );
+    }
+    if f.index {
+        bail!(
+            "The index does not cover {} (roots: {}). Add it to [index] roots and run `ldx index`.",
+            dir.display(),
+            index
+                .roots
+                .iter()
+                .map(|r| r.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    Ok(None)
+}
+
+/// One line after "Searching in:" so index answers never pass for live ones.
+pub fn print_note(index: &Index, f: &ParsedFlags, config: &IndexConfig) {
+    if is_stale(index, config) {
+        eprintln!(
+            "{} index built {}; run `ldx index` to refresh or --live to scan.",
+            warning("stale:"),
+            fmt_age(index.age())
+        );
+    } else if !f.quiet {
+        println!("(from index, built {})", fmt_age(index.age()));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ldx index [--full] [-S] | --status
+// ---------------------------------------------------------------------------
+
+pub fn run(args: &[String], config: &LdxConfig) -> Result<()> {
+    let path = index_path();
+
+    if args.iter().any(|a| a == "--status") {
+        let index = Index::load(&path)?;
+        let files = index
+            .entries
+            .iter()
+            .filter(|e| matches!(e.kind, EntryKind::File))
+            .count();
+        println!("Index: {}", path.display());
+        for root in &index.roots {
+            println!("  root: {}", root.display());
+        }
+        println!(
+            "  {} entries ({} files + {} dirs), built {}",
+            fmt_num(index.entries.len()),
+            fmt_num(files),
+            fmt_num(index.entries.len() - files),
+            fmt_age(index.age())
+        );
+        if is_stale(&index, &config.index) {
+            println!("  {} run `ldx index` to refresh", warning("stale:"));
+        }
+        return Ok(());
+    }
+    let full = args.iter().any(|a| a == "--full");
+    let stats = args.iter().any(|a| a == "-S" || a == "--stats");
+    if let Some(arg) = args
+        .iter()
+        .find(|a| !matches!(a.as_str(), "--full" | "-S" | "--stats"))
+    {
+        bail!(
+            "Unknown argument: {:?}. Usage: ldx index [--full] [-S] | ldx index --status",
+            arg
+        );
+    }
+
+    let roots = roots(&config.index);
+    let exclude = config.index.exclude.clone().unwrap_or_default();
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4);
+    // an unreadable index or a change of roots or excludes means starting over
+    let previous = match Index::load(&path) {
+        Ok(index) if !full && index.roots == roots && index.exclude == exclude => Some(index),
+        _ => None,
+    };
+
+    let start = Instant::now();
+    let Some(previous) = previous else {
+        for root in &roots {
+            println!("Indexing: {}", root.display());
+        }
+        let index = build(&roots, &exclude, threads);
+        index.save(&path)?;
+        println!(
+            "Indexed {} entries in {:.3}s → {}",
+            fmt_num(index.entries.len()),
+            start.elapsed().as_secs_f64(),
+            path.display()
+        );
+        return Ok(());
+    };
+
+    let (index, refreshed) = refresh(previous, &exclude, threads);
+    index.save(&path)?;
+    println!(
+        "Refreshed {} entries (+{} -{}) in {:.3}s → {}",
+        fmt_num(index.entries.len()),
+        fmt_num(refreshed.added),
+        fmt_num(refreshed.removed),
+        start.elapsed().as_secs_f64(),
+        path.display()
+    );
+    if stats {
+        println!(
+            "Rescanned {} dirs | Skipped {} dirs | Threads: {}",
+            fmt_num(refreshed.rescanned),
+            fmt_num(refreshed.skipped),
+            threads
+        );
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tempdir::TempDir;
+
+    fn entry(path: &str, kind: EntryKind) -> IndexEntry {
+        IndexEntry {
+            path: path.into(),
+            kind,
+            size: 42,
+            mtime: 1_700_000_000,
+        }
+    }
+
+    fn sample() -> Index {
+        Index {
+            built_at: 1_700_000_000,
+            roots: vec!["/r".into()],
+            exclude: vec!["node_modules".into()],
+            entries: vec![
+                entry("/r/a", EntryKind::Dir),
+                entry("/r/a/invoice.pdf", EntryKind::File),
+                entry("/r/a/target", EntryKind::Dir),
+                entry("/r/a/target/invoice.o", EntryKind::File),
+                entry("/r/a-b", EntryKind::Dir),
+                entry("/r/a-b/invoice.txt", EntryKind::File),
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trips_through_the_encoding() {
+        let index = sample();
+        let decoded = Index::decode(&index.encode()).unwrap();
+        assert_eq!(decoded.built_at, index.built_at);
+        assert_eq!(decoded.roots, index.roots);
+        assert_eq!(decoded.exclude, index.exclude);
+        let paths: Vec<&Path> = decoded.entries.iter().map(|e| e.path.as_path()).collect();
+        assert_eq!(paths[3], Path::new("/r/a/target/invoice.o"));
+        assert_eq!(decoded.entries[5].size, 42);
+        assert!(Index::decode(b"nope").is_err());
+    }
+
+    #[test]
+    fn answers_searches_outside_the_skipped_directories() {
+        let index = sample();
+        assert!(index.answers(Path::new("/r/a")));
+        assert!(index.answers(Path::new("/r")));
+        assert!(!index.answers(Path::new("/r/node_modules/x")));
+        assert!(!index.answers(Path::new("/elsewhere")));
+        assert_eq!(
+            skipped_in(&index.roots, &index.exclude, Path::new("/r/node_modules/x")),
+            Some("node_modules")
+        );
+    }
+
+    #[test]
+    fn query_scopes_to_dir_and_honors_excludes() {
+        let config = Config {
+            pattern: Some("invoice".into()),
+            collect_paths: true,
+            exclude: vec!["target".into()],
+            ..Config::default()
+        };
+        let result = query(&sample(), Path::new("/r/a"), &config);
+        assert_eq!(result.paths, [PathBuf::from("/r/a/invoice.pdf")]);
+    }
+
+    #[test]
+    fn dash_i_works_with_the_default_exclude_list() {
+        let config: LdxConfig = toml::from_str(crate::config::DEFAULT_CONFIG).unwrap();
+        let skipped = config.index.exclude.unwrap();
+        let root = TempDir::new("default-exclude");
+        for dir in ["src", ".git/objects", "node_modules/pkg"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join("invoice.txt"), "").unwrap();
+        }
+        let index = build(&[root.to_path_buf()], &skipped, 2);
+        let f = ParsedFlags {
+            index: true,
+            ..ParsedFlags::default()
+        };
+
+        let index = usable(index, &root, &f).unwrap().unwrap();
+        let search = Config {
+            pattern: Some("invoice".into()),
+            collect_paths: true,
+            ..Config::default()
+        };
+        let result = query(&index, &root, &search);
+        assert_eq!(result.paths, [root.join("src/invoice.txt")]);
+
+        let Err(err) = usable(index, &root.join(".git/objects"), &f) else {
+            panic!("a search inside .git was answered from the index");
+        };
+        assert!(err.to_string().contains("inside .git"), "{}", err);
+    }
+
+    #[test]
+    fn refresh_rereads_only_changed_directories() {
+        let root = TempDir::new("refresh");
+        std::fs::create_dir_all(root.join("keep")).unwrap();
+        std::fs::create_dir_all(root.join("busy/old")).unwrap();
+        std::fs::write(root.join("keep/a.txt"), "").unwrap();
+        let mut index = build(&[root.to_path_buf()], &[], 2);
+
+        std::fs::remove_dir_all(root.join("busy/old")).unwrap();
+        std::fs::write(root.join("busy/new.txt"), "").unwrap();
+        // pretend `busy` was last seen long ago and the build is long past
+        for e in &mut index.entries {
+            if e.path == root.join("busy") {
+                e.mtime = 0;
+            }
+        }
+        index.built_at = u64::MAX;
+
+        let (index, stats) = refresh(index, &[], 2);
+        let names: Vec<&Path> = index
+            .entries
+            .iter()
+            .filter_map(|e| e.path.strip_prefix(&*root).ok())
+            .collect();
+        assert_eq!(
+            names,
+            ["", "busy", "busy/new.txt", "keep", "keep/a.txt"].map(Path::new)
+        );
+        assert_eq!((stats.rescanned, stats.skipped), (1, 2));
+        assert_eq!((stats.added, stats.removed), (1, 1));
+    }
+}
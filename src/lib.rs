@@ -0,0 +1,36 @@
+//! The search engine behind `ldx`, usable on its own.
+//!
+//! Build a [`Query`], then either stream matches with [`Query::iter`] or run
+//! it to completion with [`Query::scan`]. Nothing in the library prints; all
+//! output belongs to the `ldx` binary.
+//!
+//! ```no_run
+//! use localdex::Query;
+//!
+//! let query = Query::new("/home/me/projects")
+//!     .extension("rs")
+//!     .exclude(["target", ".git"])
+//!     .with_metadata(true);
+//!
+//! for m in &query {
+//!     let size = m.metadata.map(|md| md.len()).unwrap_or(0);
+//!     println!("{:>8}  {}", size, m.path.display());
+//! }
+//! ```
+//!
+//! Lower layers stay public for tools that need them: [`DirectorySource`]
+//! implements [`parex::Source`] and can fold per-thread accumulators, and
+//! [`matcher_for`] returns the exact matcher the CLI uses.
+
+pub mod query;
+pub mod search;
+pub mod source;
+#[cfg(test)]
+mod tempdir;
+
+pub use parex::{Entry, EntryKind, ParexError};
+pub use query::{Match, Matches, Query};
+pub use search::{
+    Config, ErrorCategory, ScanResult, SpanMatcher, error_reason, matcher_for, scan_dir, scan_roots,
+};
+pub use source::DirectorySource;
@@ -1,34 +1,59 @@
 mod bench;
+mod cancel;
 mod cli;
 mod config;
-mod search;
-mod source;
+mod daemon;
+mod exec;
+mod exit;
+mod frecency;
+mod group;
+mod index;
+mod ops;
+mod sort;
+#[cfg(test)]
+mod tempdir;
+mod top;
+mod watch;
 
-use anyhow::Result;
 use dirs::home_dir;
-use std::path::PathBuf;
-
-#[cfg(windows)]
-use cli::display::fmt_num;
-use cli::display::{print_help, print_result, print_stats};
+use localdex::{search, source};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use cancel::{Budget, Stop};
+use cli::display::{display_path, fmt_num};
+use cli::display::{
+    print_exec, print_groups, print_help, print_matches, print_result, print_stats, print_stop,
+    print_top, print_total,
+};
 use cli::flags::{ParsedFlags, parse_args};
-use cli::launcher::{open_file, prompt_and_open};
-use colored::Colorize;
+use cli::launcher::{choose, open_files};
+use cli::theme::warning;
 use config::{check_config, config_path, load_config, reset_config, sync_config};
-use search::{Config, ScanResult, scan_dir};
+use exit::Failure;
+use group::scan_groups;
+use localdex::ParexError;
+use search::{Config, ErrorCategory, ScanResult, error_reason, matcher_for, scan_dir, scan_roots};
+use sort::{SortKey, SortSpec, sort_paths};
 #[cfg(windows)]
 use source::get_all_drives;
-#[cfg(windows)]
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use top::scan_top;
+
+/// Tree siblings follow result order, so give `--tree` a stable default.
+const DEFAULT_TREE_SORT: SortSpec = SortSpec {
+    key: SortKey::Path,
+    descending: false,
+};
 
 // ---------------------------------------------------------------------------
 // Build search::Config from parsed flags
 // ---------------------------------------------------------------------------
 
-fn build_search_config(f: &ParsedFlags, collect_paths: bool) -> Config {
+fn build_search_config(f: &ParsedFlags, collect_paths: bool, budget: &Budget) -> Config {
     Config {
         case_sensitive: f.case_sensitive,
-        quiet: f.quiet,
         all: f.all,
         dirs_only: f.dirs_only,
         extension: f.extension.clone(),
@@ -36,8 +61,15 @@ fn build_search_config(f: &ParsedFlags, collect_paths: bool) -> Config {
         limit: f.limit,
         threads: f.threads,
         collect_paths,
-        collect_errors: f.warn,
+        // always: skipped paths decide between exit codes 0/1 and 4
+        collect_errors: true,
         exclude: f.exclude.clone(),
+        cancel: Some(budget.flag()),
+        // the clock starts now, before any daemon or index lookup; a limit
+        // past what the clock can represent is no limit at all
+        deadline: f
+            .timeout
+            .and_then(|limit| Instant::now().checked_add(limit)),
     }
 }
 
@@ -83,12 +115,38 @@ fn resolve_dir(dir: PathBuf) -> PathBuf {
     dir
 }
 
+/// Every root has to be a directory before any walk starts; otherwise a
+/// typo in `-d` would only show up as a skipped path and exit 4.
+fn check_roots(roots: &[PathBuf]) -> Result<(), Failure> {
+    for root in roots {
+        match std::fs::metadata(root) {
+            Ok(meta) if meta.is_dir() => {}
+            Ok(_) => {
+                return Err(exit::usage(anyhow::anyhow!(
+                    "Not a directory: {}",
+                    root.display()
+                )));
+            }
+            Err(e) => {
+                return Err(exit::usage(
+                    anyhow::Error::from(e).context(format!("Cannot search {}", root.display())),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn labels_for(paths: &[PathBuf], root: &std::path::Path, f: &ParsedFlags) -> Vec<String> {
+    paths.iter().map(|p| display_path(p, root, f)).collect()
+}
+
 // ---------------------------------------------------------------------------
 // Print warnings from recoverable errors
 // ---------------------------------------------------------------------------
 
 fn print_warnings(result: &ScanResult, f: &ParsedFlags) {
-    if !f.warn || result.errors.is_empty() {
+    if !f.warn || f.json || result.errors.is_empty() {
         return;
     }
     eprintln!(
@@ -96,9 +154,26 @@ fn print_warnings(result: &ScanResult, f: &ParsedFlags) {
         result.errors.len(),
         if result.errors.len() == 1 { "" } else { "s" }
     );
+    let mut grouped: BTreeMap<ErrorCategory, Vec<&ParexError>> = BTreeMap::new();
     for err in &result.errors {
-        if let Some(path) = err.path() {
-            eprintln!("  {} {}", "skipped:".yellow(), path.display());
+        grouped.entry(ErrorCategory::of(err)).or_default().push(err);
+    }
+    for (category, errors) in grouped {
+        eprintln!(
+            "  {} {}",
+            warning(&format!("{}:", category.label())),
+            fmt_num(errors.len())
+        );
+        for err in errors {
+            let path = err
+                .path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default();
+            if f.verbose {
+                eprintln!("    {}  ({})", path, error_reason(err));
+            } else {
+                eprintln!("    {}", path);
+            }
         }
     }
 }
@@ -107,32 +182,77 @@ fn print_warnings(result: &ScanResult, f: &ParsedFlags) {
 // Main
 // ---------------------------------------------------------------------------
 
-fn main() -> Result<()> {
-    let ldx_config = load_config()?;
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(Failure { code, error }) => {
+            eprintln!("Error: {error:?}");
+            ExitCode::from(code)
+        }
+    }
+}
+
+fn run() -> Result<ExitCode, Failure> {
+    let ldx_config = load_config().map_err(exit::config)?;
 
     // ── Subcommand: bench ─────────────────────────────────────────────────────
-    let raw: Vec<String> = std::env::args().skip(1).collect();
+    let mut raw: Vec<String> = std::env::args().skip(1).collect();
+    // the shell wrapper always leads with --cd-file FILE
+    let mut cd_file: Option<PathBuf> = None;
+    if raw.first().map(|s| s.as_str()) == Some("--cd-file") {
+        cd_file = raw.get(1).map(PathBuf::from);
+        raw.drain(..raw.len().min(2));
+    }
     if raw.first().map(|s| s.as_str()) == Some("bench") {
-        return bench::run(&raw[1..], &ldx_config);
+        bench::run(&raw[1..], &ldx_config)?;
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let f = parse_args(&ldx_config)?;
+    // ── Subcommand: init ──────────────────────────────────────────────────────
+    if raw.first().map(|s| s.as_str()) == Some("init") {
+        cli::shell::run(&raw[1..])?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // ── Subcommand: daemon ────────────────────────────────────────────────────
+    if raw.first().map(|s| s.as_str()) == Some("daemon") {
+        daemon::run(&raw[1..], &ldx_config)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // ── Subcommand: index ─────────────────────────────────────────────────────
+    if raw.first().map(|s| s.as_str()) == Some("index") {
+        index::run(&raw[1..], &ldx_config)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // ── Subcommand: jump ──────────────────────────────────────────────────────
+    if raw.first().map(|s| s.as_str()) == Some("jump") {
+        frecency::run(&raw[1..], cd_file.as_deref())?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut f = parse_args(&ldx_config).map_err(exit::usage)?;
+    f.relative_to = f.relative_to.take().map(resolve_dir);
+    f.copy_to = f.copy_to.take().map(resolve_dir);
+    f.move_to = f.move_to.take().map(resolve_dir);
+    cli::theme::init(&ldx_config.theme, f.color, f.hyperlink);
 
     // ── Management flags ──────────────────────────────────────────────────────
 
     if f.show_help {
         print_help(&ldx_config);
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     if f.show_version {
         println!("localdex v{}", env!("CARGO_PKG_VERSION"));
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     if f.show_config {
         println!("Config: {}", config_path().display());
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     if f.edit_config {
@@ -150,146 +270,316 @@ fn main() -> Result<()> {
         #[cfg(target_os = "linux")]
         std::process::Command::new("xdg-open").arg(&path).spawn()?;
 
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     if f.check_config {
-        check_config(&ldx_config);
-        return Ok(());
+        check_config(&ldx_config).map_err(exit::config)?;
+        return Ok(ExitCode::SUCCESS);
     }
 
     if f.sync_config {
-        sync_config()?;
-        return Ok(());
+        sync_config().map_err(exit::config)?;
+        return Ok(ExitCode::SUCCESS);
     }
 
     if f.reset_config {
-        reset_config()?;
-        return Ok(());
+        reset_config().map_err(exit::config)?;
+        return Ok(ExitCode::SUCCESS);
     }
 
     // ── Search ────────────────────────────────────────────────────────────────
 
-    if !f.all_drives {
-        let dir = resolve_dir(f.dir.clone());
+    let budget = Budget::install();
+
+    let roots: Vec<PathBuf> = match f.all_drives {
+        #[cfg(windows)]
+        true => get_all_drives(),
+        _ => f.dirs.iter().cloned().map(resolve_dir).collect(),
+    };
+    check_roots(&roots)?;
+
+    if roots.len() == 1 && !f.all_drives {
+        let dir = roots[0].clone();
 
         if !f.quiet {
             println!("Searching in: {}", dir.display());
         }
 
-        let collect_paths = !f.quiet && !f.all || f.open || f.where_mode;
-        let config = build_search_config(&f, collect_paths);
-        let result = scan_dir(&dir, &config);
+        if let Some(n) = f.top {
+            let config = build_search_config(&f, false, &budget);
+            budget.start();
+            let top = scan_top(&dir, &config, n, f.rank_by.unwrap_or_default());
+            print_top(&top, &dir, &f, matcher_for(&config).as_ref(), &budget);
+            let stop = budget.finish();
+            print_result(&top.scan, top.scan.matches, &f, "");
+            print_stop(stop, "");
+            print_stats(&top.scan, &f, "");
+            print_warnings(&top.scan, &f);
+            return Ok(exit::for_search(
+                top.scan.matches,
+                top.scan.errors.len(),
+                stop,
+            ));
+        }
+
+        if let Some(key) = f.group_by {
+            let config = build_search_config(&f, false, &budget);
+            budget.start();
+            let groups = scan_groups(&dir, &config, key, f.bytes);
+            print_groups(&groups, &f, &budget);
+            let stop = budget.finish();
+            print_result(&groups.scan, groups.scan.matches, &f, "");
+            print_stop(stop, "");
+            print_stats(&groups.scan, &f, "");
+            print_warnings(&groups.scan, &f);
+            return Ok(exit::for_search(
+                groups.scan.matches,
+                groups.scan.errors.len(),
+                stop,
+            ));
+        }
+
+        let collect_paths = !f.quiet && !f.all
+            || f.open
+            || f.where_mode
+            || f.exec.is_some()
+            || f.delete
+            || f.copy_to.is_some()
+            || f.move_to.is_some()
+            || f.watch
+            || f.json;
+        let config = build_search_config(&f, collect_paths, &budget);
+        let from_daemon = if f.live {
+            None
+        } else {
+            daemon::query(&dir, &config)
+        };
+        let mut result = match from_daemon {
+            Some(result) => {
+                if !f.quiet {
+                    println!("(from ldx daemon)");
+                }
+                result
+            }
+            None => match index::for_search(&dir, &f, &ldx_config.index)? {
+                Some(idx) => {
+                    index::print_note(&idx, &f, &ldx_config.index);
+                    index::query(&idx, &dir, &config)
+                }
+                None => {
+                    budget.start();
+                    let result = scan_dir(&dir, &config).map_err(anyhow::Error::from)?;
+                    budget.finish();
+                    result
+                }
+            },
+        };
+        let stop = budget.stopped();
         let reported_matches = clamp_matches(&result, f.limit);
+        let code = exit::for_search(reported_matches, result.errors.len(), stop);
+
+        if let Some(spec) = f.sort.or(f.tree.then_some(DEFAULT_TREE_SORT)) {
+            sort_paths(&mut result.paths, spec, f.reverse);
+        }
 
+        if f.json {
+            println!(
+                "{}",
+                cli::json::render(
+                    &result,
+                    &dir,
+                    f.relative_to.as_deref(),
+                    reported_matches,
+                    stop
+                )
+            );
+            return Ok(code);
+        }
+
+        // an interrupted search reports what it found and does nothing else
+        if stop == Some(Stop::Interrupted) {
+            list_matches(&result, &dir, &f, &config, &budget);
+            print_result(&result, reported_matches, &f, "");
+            print_stop(stop, "");
+            print_stats(&result, &f, "");
+            print_warnings(&result, &f);
+            return Ok(code);
+        }
+
+        if f.pick && result.paths.len() > 1 {
+            result.paths = choose(&result.paths, labels_for(&result.paths, &dir, &f))?;
+            if result.paths.is_empty() {
+                return Ok(ExitCode::from(exit::NO_MATCHES));
+            }
+        }
+
+        if let Some(template) = &f.exec {
+            let summary = exec::run(template, &result.paths, f.jobs);
+            print_result(&result, reported_matches, &f, "");
+            print_stop(stop, "");
+            print_exec(&summary);
+            print_stats(&result, &f, "");
+            print_warnings(&result, &f);
+            if summary.failed > 0 {
+                // a child's own code could collide with ours (1 would read as
+                // "no matches"), so any failure is reported as one
+                return Ok(ExitCode::from(exit::FAILURE));
+            }
+            return Ok(code);
+        }
+
+        let relocate = match (&f.copy_to, &f.move_to) {
+            (Some(dest), _) => Some((dest, ops::Transfer::Copy)),
+            (_, Some(dest)) => Some((dest, ops::Transfer::Move)),
+            _ => None,
+        };
+        if f.delete || relocate.is_some() {
+            let failed = match relocate {
+                Some((dest, mode)) => ops::relocate_matches(&result.paths, &dir, dest, mode, &f)?,
+                None => ops::delete_matches(&result.paths, &dir, &f)?,
+            };
+            print_result(&result, reported_matches, &f, "");
+            print_stop(stop, "");
+            print_stats(&result, &f, "");
+            print_warnings(&result, &f);
+            if failed > 0 {
+                return Ok(ExitCode::from(exit::FAILURE));
+            }
+            return Ok(code);
+        }
+
+        let stop = list_matches(&result, &dir, &f, &config, &budget);
         print_result(&result, reported_matches, &f, "");
+        print_stop(stop, "");
         print_stats(&result, &f, "");
         print_warnings(&result, &f);
+        let code = exit::for_search(reported_matches, result.errors.len(), stop);
+        // a listing cut short by Ctrl-C does nothing further either
+        if stop == Some(Stop::Interrupted) {
+            return Ok(code);
+        }
+
+        if f.watch {
+            watch::run(&dir, &config, &f, result.paths)?;
+            return Ok(ExitCode::SUCCESS);
+        }
 
         if reported_matches == 0 {
-            std::process::exit(1);
+            return Ok(code);
         }
 
         if f.open {
-            match result.paths.len() {
-                0 => {}
-                1 => open_file(&result.paths[0])?,
-                _ => prompt_and_open(&result.paths)?,
-            }
+            let chosen = if result.paths.len() > 1 && !f.pick {
+                choose(&result.paths, labels_for(&result.paths, &dir, &f))?
+            } else {
+                result.paths.clone()
+            };
+            open_files(&chosen, &ldx_config, &f)?;
+            frecency::remember(chosen.iter().filter_map(|p| p.parent()));
         }
 
         if f.where_mode {
-            match result.paths.len() {
-                0 => {}
-                1 => {
-                    let path = &result.paths[0];
-                    let dir_path = if f.dirs_only {
-                        path.to_string_lossy().into_owned()
-                    } else {
-                        path.parent()
-                            .map(|p| p.to_string_lossy().into_owned())
-                            .unwrap_or_else(|| path.to_string_lossy().into_owned())
-                    };
-                    println!("  → cd {}", dir_path);
+            let target = match result.paths.len() {
+                0 => None,
+                1 => Some(result.paths[0].clone()),
+                _ if f.cd_file.is_some() => {
+                    choose(&result.paths, labels_for(&result.paths, &dir, &f))?
+                        .into_iter()
+                        .next()
+                }
+                _ => {
+                    println!("  → Multiple results found, use -1 to get a single match");
+                    None
+                }
+            };
+            if let Some(path) = target {
+                let dir_path = if f.dirs_only {
+                    path.clone()
+                } else {
+                    path.parent().map(|p| p.to_path_buf()).unwrap_or(path)
+                };
+                frecency::remember([dir_path.as_path()]);
+                match &f.cd_file {
+                    Some(file) => std::fs::write(file, dir_path.to_string_lossy().as_bytes())?,
+                    None => println!("  → cd {}", dir_path.display()),
                 }
-                _ => println!("  → Multiple results found, use -1 to get a single match"),
             }
         }
-    } else {
-        #[cfg(windows)]
-        {
-            let drives = get_all_drives();
-            let config = build_search_config(&f, f.open);
-            let total_start = Instant::now();
-            let mut total_matches = 0usize;
-            let mut total_files = 0usize;
-            let mut total_dirs = 0usize;
-
-            for drive in &drives {
-                if !f.quiet {
-                    println!("Searching in: {}", drive.display());
-                }
-                let result = scan_dir(drive, &config);
-                total_matches += result.matches;
-                total_files += result.files;
-                total_dirs += result.dirs;
+        return Ok(code);
+    }
 
-                print_result(&result, result.matches, &f, "  ");
-                print_stats(&result, &f, "  ");
-            }
+    search_roots(&roots, &f, &budget)
+}
 
-            let total_dur = total_start.elapsed();
-            let total_tc = total_files + total_dirs;
-            let s = total_dur.as_secs_f64();
-
-            println!();
-            if f.all {
-                println!(
-                    "Total: {} file{} across {} drive{} in {:.3}s",
-                    fmt_num(total_matches),
-                    if total_matches == 1 { "" } else { "s" },
-                    drives.len(),
-                    if drives.len() == 1 { "" } else { "s" },
-                    s
-                );
-            } else {
-                println!(
-                    "Total: {} matching file{} across {} drive{} in {:.3}s",
-                    fmt_num(total_matches),
-                    if total_matches == 1 { "" } else { "s" },
-                    drives.len(),
-                    if drives.len() == 1 { "" } else { "s" },
-                    s
-                );
-            }
+/// Prints the matches with Ctrl-C latched, so an interrupt cuts the listing
+/// short and the summary still follows. Returns why the search or the
+/// listing ended early, if either did.
+fn list_matches(
+    result: &ScanResult,
+    root: &Path,
+    f: &ParsedFlags,
+    config: &Config,
+    budget: &Budget,
+) -> Option<Stop> {
+    budget.start();
+    print_matches(result, root, f, matcher_for(config).as_ref(), budget);
+    budget.finish()
+}
 
-            if f.stats && s > 0.0 {
-                if f.verbose {
-                    println!(
-                        "Scanned {} entries ({} files + {} dirs) | Speed: {} entries/s | Threads: {}",
-                        fmt_num(total_tc),
-                        fmt_num(total_files),
-                        fmt_num(total_dirs),
-                        fmt_num((total_tc as f64 / s) as usize),
-                        f.threads
-                    );
-                } else {
-                    println!(
-                        "Scanned {} entries | {} entries/s | Threads: {}",
-                        fmt_num(total_tc),
-                        fmt_num((total_tc as f64 / s) as usize),
-                        f.threads
-                    );
-                }
-            }
+// ---------------------------------------------------------------------------
+// Several roots (repeated -d, or -A on Windows): scanned together, printed
+// one after another, then totalled
+// ---------------------------------------------------------------------------
 
-            if total_matches == 0 {
-                std::process::exit(1);
-            }
+fn search_roots(roots: &[PathBuf], f: &ParsedFlags, budget: &Budget) -> Result<ExitCode, Failure> {
+    let config = build_search_config(f, !f.quiet && !f.all, budget);
+    let start = Instant::now();
+    // latched until every root is listed
+    budget.start();
+    let results = scan_roots(roots, &config);
+
+    let mut total = ScanResult {
+        matches: 0,
+        files: 0,
+        dirs: 0,
+        duration: Duration::ZERO,
+        paths: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (root, result) in roots.iter().zip(results) {
+        let mut result = result.map_err(anyhow::Error::from)?;
+        if let Some(spec) = f.sort.or(f.tree.then_some(DEFAULT_TREE_SORT)) {
+            sort_paths(&mut result.paths, spec, f.reverse);
         }
+        let reported_matches = clamp_matches(&result, f.limit);
+        if !f.quiet {
+            println!("Searching in: {}", root.display());
+        }
+        print_matches(&result, root, f, matcher_for(&config).as_ref(), budget);
+        print_result(&result, reported_matches, f, "  ");
+        print_stats(&result, f, "  ");
+
+        total.matches += reported_matches;
+        total.files += result.files;
+        total.dirs += result.dirs;
+        total.errors.append(&mut result.errors);
     }
+    total.duration = start.elapsed();
+    let stop = budget.finish();
+
+    println!();
+    print_total(
+        &total,
+        roots.len(),
+        if f.all_drives { "drive" } else { "root" },
+        f,
+    );
+    print_stop(stop, "");
+    print_stats(&total, f, "");
+    print_warnings(&total, f);
 
-    Ok(())
+    Ok(exit::for_search(total.matches, total.errors.len(), stop))
 }
 
 // ---------------------------------------------------------------------------
@@ -0,0 +1,606 @@
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::cli::display::{display_path, fmt_num, fmt_size};
+use crate::cli::flags::ParsedFlags;
+use crate::cli::theme::{paint_name, percent_encode, success, warning};
+
+// ---------------------------------------------------------------------------
+// Targets — matches with anything already covered by a matched ancestor
+// directory dropped, so nothing is removed twice
+// ---------------------------------------------------------------------------
+
+pub fn outermost(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut sorted: Vec<&PathBuf> = paths.iter().collect();
+    sorted.sort();
+    let mut out: Vec<PathBuf> = Vec::new();
+    for path in sorted {
+        if out.last().is_some_and(|kept| path.starts_with(kept)) {
+            continue;
+        }
+        out.push(path.clone());
+    }
+    out
+}
+
+/// Bytes on disk under `path`; symlinks count as themselves, never followed.
+pub fn disk_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|rd| rd.flatten().map(|e| disk_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+// ---------------------------------------------------------------------------
+// --delete
+// ---------------------------------------------------------------------------
+
+pub fn remove(path: &Path) -> Result<()> {
+    let meta = std::fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        std::fs::remove_dir_all(path)?;
+    } else {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// --trash — freedesktop.org trash spec, home trash only
+// ---------------------------------------------------------------------------
+
+/// `$XDG_DATA_HOME/Trash`. Only meaningful where the desktop follows the
+/// spec, which is why `--trash` is refused on macOS and Windows.
+fn trash_dir() -> Result<PathBuf> {
+    let data = dirs::data_dir().context("Could not locate the XDG data directory.")?;
+    Ok(data.join("Trash"))
+}
+
+pub fn trash(path: &Path) -> Result<()> {
+    trash_into(path, &trash_dir()?)
+}
+
+fn trash_into(path: &Path, trash: &Path) -> Result<()> {
+    let files = trash.join("files");
+    let info = trash.join("info");
+    std::fs::create_dir_all(&files)?;
+    std::fs::create_dir_all(&info)?;
+
+    let name = path
+        .file_name()
+        .with_context(|| format!("{} has no file name", path.display()))?
+        .to_string_lossy()
+        .into_owned();
+
+    // claim a unique .trashinfo first; create_new makes the claim atomic
+    let mut n = 1;
+    let (stored, info_file) = loop {
+        let stored = if n == 1 {
+            name.clone()
+        } else {
+            format!("{}.{}", name, n)
+        };
+        let info_path = info.join(format!("{}.trashinfo", stored));
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(file) if !files.join(&stored).exists() => break (stored, (info_path, file)),
+            Ok(_) => {
+                let _ = std::fs::remove_file(&info_path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+        n += 1;
+    };
+
+    let (info_path, mut file) = info_file;
+    let contents = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        percent_encode(&path.to_string_lossy()),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    std::io::Write::write_all(&mut file, contents.as_bytes())?;
+
+    if let Err(e) = std::fs::rename(path, files.join(&stored)) {
+        let _ = std::fs::remove_file(&info_path);
+        if e.kind() == std::io::ErrorKind::CrossesDevices {
+            bail!("not on the same filesystem as {}", trash.display());
+        }
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Preview, confirm, apply — returns how many entries failed
+// ---------------------------------------------------------------------------
+
+pub fn delete_matches(paths: &[PathBuf], root: &Path, f: &ParsedFlags) -> Result<usize> {
+    let targets = outermost(paths);
+    if targets.is_empty() {
+        return Ok(0);
+    }
+    let total: u64 = targets.iter().map(|p| disk_size(p)).sum();
+    let count = format!(
+        "{} entr{} ({})",
+        fmt_num(targets.len()),
+        if targets.len() == 1 { "y" } else { "ies" },
+        fmt_size(total)
+    );
+    let verb = if f.trash { "Trash" } else { "Delete" };
+
+    for path in &targets {
+        println!("  {}", paint_name(&display_path(path, root, f), path));
+    }
+    println!();
+
+    if f.dry_run {
+        println!(
+            "{} would {} {} — nothing was changed.",
+            warning("Dry run:").bold(),
+            verb.to_lowercase(),
+            count
+        );
+        return Ok(0);
+    }
+
+    if !f.yes {
+        print!(
+            "{} {}.\n  Continue? [y/N] ",
+            warning(&format!("{}:", verb)).bold(),
+            count
+        );
+        std::io::Write::flush(&mut std::io::stdout())?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("{} cancelled.", verb);
+            return Ok(0);
+        }
+    }
+
+    let mut failed = 0usize;
+    for path in &targets {
+        let outcome = if f.trash { trash(path) } else { remove(path) };
+        if let Err(e) = outcome {
+            eprintln!("  {} {}: {}", warning("failed:"), path.display(), e);
+            failed += 1;
+        }
+    }
+
+    let done = targets.len() - failed;
+    println!(
+        "{} {} entr{} {}.",
+        success("Done:").bold(),
+        fmt_num(done),
+        if done == 1 { "y" } else { "ies" },
+        if f.trash { "moved to trash" } else { "deleted" }
+    );
+    Ok(failed)
+}
+
+// ---------------------------------------------------------------------------
+// --copy-to / --move-to
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transfer {
+    Copy,
+    Move,
+}
+
+/// What to do when the destination already exists — parsed from
+/// --on-conflict skip|overwrite|rename.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Conflict {
+    #[default]
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+impl FromStr for Conflict {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "skip" => Ok(Conflict::Skip),
+            "overwrite" => Ok(Conflict::Overwrite),
+            "rename" => Ok(Conflict::Rename),
+            _ => bail!(
+                "Invalid --on-conflict value: {:?}. Expected skip, overwrite or rename.",
+                s
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    Write,
+    Overwrite,
+    Skip,
+}
+
+/// Where `src` lands under `dest`: its path below `root`, or just its name
+/// when flattening.
+fn destination(src: &Path, root: &Path, dest: &Path, flatten: bool) -> PathBuf {
+    let rel = if flatten {
+        Path::new(src.file_name().unwrap_or(src.as_os_str()))
+    } else {
+        src.strip_prefix(root).unwrap_or(src)
+    };
+    dest.join(rel)
+}
+
+/// `report.pdf` → `report-1.pdf`, `report-2.pdf`, … until `taken` says no.
+fn with_suffix(path: &Path, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{}-{}{}", stem, n, ext)))
+        .find(|p| !taken(p))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Decides every destination up front so flattened name clashes within one
+/// run are caught by --dry-run too.
+fn plan(
+    targets: &[PathBuf],
+    root: &Path,
+    dest: &Path,
+    flatten: bool,
+    conflict: Conflict,
+) -> Vec<(PathBuf, PathBuf, Step)> {
+    let mut claimed: HashSet<PathBuf> = HashSet::new();
+    let taken = |p: &Path, claimed: &HashSet<PathBuf>| {
+        claimed.contains(p) || std::fs::symlink_metadata(p).is_ok()
+    };
+    targets
+        .iter()
+        .map(|src| {
+            let dst = destination(src, root, dest, flatten);
+            let (dst, step) = if !taken(&dst, &claimed) {
+                (dst, Step::Write)
+            } else {
+                match conflict {
+                    Conflict::Skip => (dst, Step::Skip),
+                    Conflict::Overwrite if claimed.contains(&dst) => (dst, Step::Skip),
+                    Conflict::Overwrite => (dst, Step::Overwrite),
+                    Conflict::Rename => (with_suffix(&dst, |p| taken(p, &claimed)), Step::Write),
+                }
+            };
+            if step != Step::Skip {
+                claimed.insert(dst.clone());
+            }
+            (src.clone(), dst, step)
+        })
+        .collect()
+}
+
+fn copy_tree(src: &Path, dst: &Path) -> Result<()> {
+    let meta = std::fs::symlink_metadata(src)?;
+    if meta.is_dir() {
+        std::fs::create_dir_all(dst)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+    } else if meta.file_type().is_symlink() {
+        copy_link(src, dst)?;
+    } else {
+        std::fs::copy(src, dst)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_link(src: &Path, dst: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(src)?, dst)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn copy_link(src: &Path, dst: &Path) -> Result<()> {
+    std::fs::copy(src, dst)?;
+    Ok(())
+}
+
+fn transfer(mode: Transfer, src: &Path, dst: &Path, step: &Step) -> Result<()> {
+    let verb = if mode == Transfer::Copy {
+        "copy"
+    } else {
+        "move"
+    };
+    if dst.starts_with(src) {
+        bail!("cannot {} a directory into itself", verb);
+    }
+    // e.g. a flattened dest/notes/notes landing on dest/notes: overwriting
+    // the directory would delete the source before it is read
+    if src.starts_with(dst) {
+        bail!("cannot {} over {}, which contains it", verb, dst.display());
+    }
+    if *step == Step::Overwrite {
+        remove(dst)?;
+    }
+    if let Some(parent) = dst.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match mode {
+        Transfer::Copy => copy_tree(src, dst),
+        Transfer::Move => match std::fs::rename(src, dst) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+                copy_tree(src, dst)?;
+                remove(src)
+            }
+            Err(e) => Err(e.into()),
+        },
+    }
+}
+
+/// Lists the plan, then applies it unless --dry-run. Returns how many
+/// entries failed.
+pub fn relocate_matches(
+    paths: &[PathBuf],
+    root: &Path,
+    dest: &Path,
+    mode: Transfer,
+    f: &ParsedFlags,
+) -> Result<usize> {
+    let targets = outermost(paths);
+    let steps = plan(&targets, root, dest, f.flatten, f.on_conflict);
+    let verb = match mode {
+        Transfer::Copy => "copy",
+        Transfer::Move => "move",
+    };
+
+    let mut failed = 0usize;
+    let mut done = 0usize;
+    let mut skipped = 0usize;
+    for (src, dst, step) in &steps {
+        let from = paint_name(&display_path(src, root, f), src);
+        if *step == Step::Skip {
+            println!("  {} {} (exists: {})", "skip".dimmed(), from, dst.display());
+            skipped += 1;
+            continue;
+        }
+        let note = if *step == Step::Overwrite {
+            " (overwrite)"
+        } else {
+            ""
+        };
+        println!("  {} → {}{}", from, dst.display(), note.dimmed());
+        if f.dry_run {
+            continue;
+        }
+        match transfer(mode, src, dst, step) {
+            Ok(()) => done += 1,
+            Err(e) => {
+                eprintln!("  {} {}: {}", warning("failed:"), src.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    println!();
+
+    let pending = steps.len() - skipped;
+    let skipped_note = if skipped > 0 {
+        format!(", {} skipped", fmt_num(skipped))
+    } else {
+        String::new()
+    };
+    if f.dry_run {
+        println!(
+            "{} would {} {} entr{}{} — nothing was changed.",
+            warning("Dry run:").bold(),
+            verb,
+            fmt_num(pending),
+            if pending == 1 { "y" } else { "ies" },
+            skipped_note
+        );
+    } else {
+        println!(
+            "{} {} entr{} {}{}.",
+            success("Done:").bold(),
+            fmt_num(done),
+            if done == 1 { "y" } else { "ies" },
+            if mode == Transfer::Copy {
+                "copied"
+            } else {
+                "moved"
+            },
+            skipped_note
+        );
+    }
+    Ok(failed)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tempdir::TempDir;
+
+    #[test]
+    fn outermost_drops_paths_inside_matched_directories() {
+        let paths: Vec<PathBuf> = ["/r/build/out.o", "/r/build", "/r/a.orig", "/r/builder"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(
+            outermost(&paths),
+            ["/r/a.orig", "/r/build", "/r/builder"]
+                .iter()
+                .map(PathBuf::from)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn destination_preserves_or_flattens() {
+        let (root, dest) = (Path::new("/r"), Path::new("/out"));
+        let src = Path::new("/r/2024/jan/invoice.pdf");
+        assert_eq!(
+            destination(src, root, dest, false),
+            PathBuf::from("/out/2024/jan/invoice.pdf")
+        );
+        assert_eq!(
+            destination(src, root, dest, true),
+            PathBuf::from("/out/invoice.pdf")
+        );
+    }
+
+    #[test]
+    fn flattened_clashes_are_planned_per_policy() {
+        let root = Path::new("/nonexistent-root");
+        let dest = Path::new("/nonexistent-dest");
+        let targets: Vec<PathBuf> = ["/nonexistent-root/a/x.pdf", "/nonexistent-root/b/x.pdf"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let skip = plan(&targets, root, dest, true, Conflict::Skip);
+        assert_eq!(skip[0].2, Step::Write);
+        assert_eq!(skip[1].2, Step::Skip);
+
+        let rename = plan(&targets, root, dest, true, Conflict::Rename);
+        assert_eq!(rename[1].1, PathBuf::from("/nonexistent-dest/x-1.pdf"));
+        assert_eq!(rename[1].2, Step::Write);
+    }
+
+    #[test]
+    fn move_and_copy_replace_existing_targets() {
+        let root = TempDir::new("transfer");
+        for (name, body) in [
+            ("src/a.txt", "new"),
+            ("dest/a.txt", "old"),
+            ("src/b.txt", "b"),
+        ] {
+            std::fs::create_dir_all(root.join(name).parent().unwrap()).unwrap();
+            std::fs::write(root.join(name), body).unwrap();
+        }
+        std::fs::create_dir_all(root.join("dest/b.txt/stale")).unwrap();
+
+        let (a, b) = (root.join("src/a.txt"), root.join("src/b.txt"));
+        transfer(
+            Transfer::Move,
+            &a,
+            &root.join("dest/a.txt"),
+            &Step::Overwrite,
+        )
+        .unwrap();
+        transfer(
+            Transfer::Copy,
+            &b,
+            &root.join("dest/b.txt"),
+            &Step::Overwrite,
+        )
+        .unwrap();
+        transfer(
+            Transfer::Move,
+            &b,
+            &root.join("dest/deep/b.txt"),
+            &Step::Write,
+        )
+        .unwrap();
+
+        let read = |p: &str| std::fs::read_to_string(root.join(p)).unwrap();
+        assert!(!a.exists() && !b.exists());
+        assert_eq!(read("dest/a.txt"), "new");
+        assert_eq!(read("dest/b.txt"), "b");
+        assert_eq!(read("dest/deep/b.txt"), "b");
+    }
+
+    #[test]
+    fn transfers_never_overwrite_their_own_source() {
+        let root = TempDir::new("transfer-self");
+        std::fs::create_dir_all(root.join("dest/notes")).unwrap();
+        std::fs::write(root.join("dest/notes/notes"), "keep").unwrap();
+        let src = root.join("dest/notes/notes");
+
+        // a flattened match landing on the directory that holds it
+        for mode in [Transfer::Move, Transfer::Copy] {
+            assert!(transfer(mode, &src, &root.join("dest/notes"), &Step::Overwrite).is_err());
+        }
+        assert!(
+            transfer(
+                Transfer::Copy,
+                &root.join("dest"),
+                &root.join("dest/x"),
+                &Step::Write
+            )
+            .is_err()
+        );
+        assert_eq!(std::fs::read_to_string(&src).unwrap(), "keep");
+    }
+
+    #[test]
+    fn remove_deletes_trees_but_not_link_targets() {
+        let root = TempDir::new("remove");
+        std::fs::create_dir_all(root.join("keep")).unwrap();
+        std::fs::create_dir_all(root.join("tree/sub")).unwrap();
+        std::fs::write(root.join("keep/a.txt"), "").unwrap();
+        std::fs::write(root.join("tree/sub/b.txt"), "").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("keep"), root.join("link")).unwrap();
+
+        remove(&root.join("tree")).unwrap();
+        assert!(!root.join("tree").exists());
+        #[cfg(unix)]
+        {
+            remove(&root.join("link")).unwrap();
+            assert!(root.join("keep/a.txt").exists());
+        }
+        assert!(remove(&root.join("missing")).is_err());
+    }
+
+    #[test]
+    fn trash_records_origin_and_never_overwrites() {
+        // stands in for $XDG_DATA_HOME
+        let data = TempDir::new("trash");
+        let trash = data.join("Trash");
+        for dir in ["one", "two"] {
+            std::fs::create_dir_all(data.join(dir)).unwrap();
+            std::fs::write(data.join(dir).join("my notes.txt"), dir).unwrap();
+        }
+
+        trash_into(&data.join("one/my notes.txt"), &trash).unwrap();
+        trash_into(&data.join("two/my notes.txt"), &trash).unwrap();
+
+        assert!(!data.join("one/my notes.txt").exists());
+        let read = |p: &str| std::fs::read_to_string(trash.join(p)).unwrap();
+        assert_eq!(read("files/my notes.txt"), "one");
+        assert_eq!(read("files/my notes.txt.2"), "two");
+        let info = read("info/my notes.txt.2.trashinfo");
+        assert!(info.starts_with("[Trash Info]\nPath="));
+        assert!(info.contains("/two/my%20notes.txt\n"));
+        assert!(info.contains("DeletionDate="));
+    }
+
+    #[test]
+    fn failed_trash_releases_its_claim() {
+        let data = TempDir::new("trash-fail");
+        let trash = data.join("Trash");
+        assert!(trash_into(&data.join("gone.txt"), &trash).is_err());
+        assert_eq!(std::fs::read_dir(trash.join("info")).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,252 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
+
+use parex::{Entry, EntryKind, ParexError};
+
+use crate::search::{Config, ScanResult, SpanMatcher, matcher_for, scan_dir, source_for};
+
+// ---------------------------------------------------------------------------
+// Query — the typed front door to the search engine
+// ---------------------------------------------------------------------------
+
+/// A search under one root, built up with chained setters.
+///
+/// With no pattern, extension or [`all_files`](Query::all_files) every file
+/// name matches. Options mirror the CLI flags of the same name.
+#[derive(Clone)]
+pub struct Query {
+    root: PathBuf,
+    config: Config,
+    metadata: bool,
+}
+
+impl Query {
+    /// Searches below `root` with one thread per logical core.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            config: Config {
+                threads: std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(4),
+                ..Config::default()
+            },
+            metadata: false,
+        }
+    }
+
+    /// Match names containing `pattern` (case-insensitive unless
+    /// [`case_sensitive`](Query::case_sensitive)).
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.config.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Match files by extension, without the dot.
+    pub fn extension(mut self, ext: impl Into<String>) -> Self {
+        self.config.extension = Some(ext.into());
+        self
+    }
+
+    /// Match every file; overrides pattern and extension.
+    pub fn all_files(mut self) -> Self {
+        self.config.all = true;
+        self
+    }
+
+    /// Match directories instead of files.
+    pub fn dirs_only(mut self) -> Self {
+        self.config.dirs_only = true;
+        self
+    }
+
+    pub fn case_sensitive(mut self, yes: bool) -> Self {
+        self.config.case_sensitive = yes;
+        self
+    }
+
+    /// Directory names to skip, with everything below them.
+    pub fn exclude<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.exclude = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Stop after `n` matches.
+    pub fn limit(mut self, n: usize) -> Self {
+        self.config.limit = Some(n);
+        self
+    }
+
+    pub fn threads(mut self, n: usize) -> Self {
+        self.config.threads = n.max(1);
+        self
+    }
+
+    /// Stop the walk once `flag` is set; [`scan`](Query::scan) then returns
+    /// what was found so far and [`iter`](Query::iter) ends early.
+    pub fn cancel(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.config.cancel = Some(flag);
+        self
+    }
+
+    /// Stop the walk at `at`; as with [`cancel`](Query::cancel), what was
+    /// found so far is kept, and the cancel flag is set if there is one.
+    pub fn deadline(mut self, at: Instant) -> Self {
+        self.config.deadline = Some(at);
+        self
+    }
+
+    /// Read `std::fs::symlink_metadata` for each match yielded by
+    /// [`iter`](Query::iter).
+    pub fn with_metadata(mut self, yes: bool) -> Self {
+        self.metadata = yes;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The matcher this query applies, e.g. to highlight matched spans.
+    pub fn matcher(&self) -> Arc<dyn SpanMatcher> {
+        matcher_for(&self.config)
+    }
+
+    /// Runs the search to completion: counts plus every matched path.
+    pub fn scan(&self) -> Result<ScanResult, ParexError> {
+        let config = Config {
+            collect_paths: true,
+            collect_errors: true,
+            ..self.config.clone()
+        };
+        scan_dir(&self.root, &config)
+    }
+
+    /// Streams matches as the walk finds them, in no particular order.
+    pub fn iter(&self) -> Matches {
+        Matches {
+            entries: Box::new(
+                source_for(&self.root, &self.config).entries(self.config.threads, None),
+            ),
+            matcher: self.matcher(),
+            remaining: self.config.limit,
+            metadata: self.metadata,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl IntoIterator for &Query {
+    type Item = Match;
+    type IntoIter = Matches;
+
+    fn into_iter(self) -> Matches {
+        self.iter()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Matches — streaming results
+// ---------------------------------------------------------------------------
+
+/// One matched entry.
+#[derive(Debug)]
+pub struct Match {
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    /// Path components below the search root; its children are depth 1.
+    pub depth: usize,
+    /// Present when the query asked for [`with_metadata`](Query::with_metadata)
+    /// and the entry could still be read.
+    pub metadata: Option<std::fs::Metadata>,
+}
+
+/// Iterator returned by [`Query::iter`].
+pub struct Matches {
+    entries: Box<dyn Iterator<Item = Result<Entry, ParexError>> + Send>,
+    matcher: Arc<dyn SpanMatcher>,
+    remaining: Option<usize>,
+    metadata: bool,
+    errors: Vec<ParexError>,
+}
+
+impl Matches {
+    /// Paths the walk could not read so far; complete once the iterator is
+    /// exhausted.
+    pub fn errors(&self) -> &[ParexError] {
+        &self.errors
+    }
+}
+
+impl Iterator for Matches {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        let entry = loop {
+            match self.entries.next()? {
+                Ok(entry) if self.matcher.is_match(&entry) => break entry,
+                Ok(_) => {}
+                Err(err) => self.errors.push(err),
+            }
+        };
+        if let Some(n) = &mut self.remaining {
+            *n -= 1;
+        }
+        let metadata = if self.metadata {
+            std::fs::symlink_metadata(&entry.path).ok()
+        } else {
+            None
+        };
+        Some(Match {
+            path: entry.path,
+            kind: entry.kind,
+            depth: entry.depth,
+            metadata,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tempdir::TempDir;
+
+    #[test]
+    fn streams_matches_with_limit_and_metadata() {
+        let root = TempDir::new("query");
+        std::fs::create_dir_all(root.join("a/target")).unwrap();
+        for name in ["a/invoice1.txt", "a/target/invoice2.txt", "invoice3.pdf"] {
+            std::fs::write(root.join(name), "12345").unwrap();
+        }
+
+        let query = Query::new(&*root).pattern("INVOICE").exclude(["target"]);
+        let mut paths: Vec<PathBuf> = query.iter().map(|m| m.path).collect();
+        paths.sort();
+        let limited = query
+            .clone()
+            .limit(1)
+            .with_metadata(true)
+            .iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths,
+            [root.join("a/invoice1.txt"), root.join("invoice3.pdf")]
+        );
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].metadata.as_ref().map(|m| m.len()), Some(5));
+    }
+}
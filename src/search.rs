@@ -1,7 +1,11 @@
-use std::path::PathBuf;
-use std::time::Duration;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use parex::Matcher;
+use parex::{EntryKind, Matcher, ParexError};
 
 use crate::source::DirectorySource;
 
@@ -15,16 +19,87 @@ pub struct ScanResult {
     pub dirs: usize,
     pub duration: Duration,
     pub paths: Vec<PathBuf>,
-    pub errors: Vec<parex::ParexError>,
+    pub errors: Vec<ParexError>,
+}
+
+// ---------------------------------------------------------------------------
+// Error categories
+// ---------------------------------------------------------------------------
+
+/// Why a path was skipped, coarse enough to group warnings by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorCategory {
+    PermissionDenied,
+    /// Listed by its parent, gone by the time it was read.
+    Vanished,
+    SymlinkLoop,
+    Io,
+}
+
+impl ErrorCategory {
+    pub fn of(err: &ParexError) -> Self {
+        match err {
+            ParexError::PermissionDenied(_) => Self::PermissionDenied,
+            ParexError::NotFound(_) => Self::Vanished,
+            ParexError::SymlinkLoop(_) => Self::SymlinkLoop,
+            ParexError::Io { source, .. } => match source.kind() {
+                io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+                io::ErrorKind::NotFound => Self::Vanished,
+                _ if is_loop(source) => Self::SymlinkLoop,
+                _ => Self::Io,
+            },
+            _ => Self::Io,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::PermissionDenied => "permission denied",
+            Self::Vanished => "vanished during walk",
+            Self::SymlinkLoop => "symlink loop",
+            Self::Io => "I/O error",
+        }
+    }
+
+    /// Stable identifier for machine-readable output.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::PermissionDenied => "permission_denied",
+            Self::Vanished => "vanished",
+            Self::SymlinkLoop => "symlink_loop",
+            Self::Io => "io",
+        }
+    }
+}
+
+/// The underlying reason, without the path: the OS message when there is one.
+pub fn error_reason(err: &ParexError) -> String {
+    match err {
+        ParexError::Io { source, .. } => source.to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn is_loop(err: &io::Error) -> bool {
+    // ELOOP
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    return err.raw_os_error() == Some(40);
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    return err.raw_os_error() == Some(62);
+    #[allow(unreachable_code)]
+    {
+        let _ = err;
+        false
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Search config
 // ---------------------------------------------------------------------------
 
+#[derive(Clone, Default)]
 pub struct Config {
     pub case_sensitive: bool,
-    pub quiet: bool,
     pub all: bool,
     pub dirs_only: bool,
     pub extension: Option<String>,
@@ -34,30 +109,98 @@ pub struct Config {
     pub collect_paths: bool,
     pub collect_errors: bool,
     pub exclude: Vec<String>,
+    /// Stops the walk early once set, e.g. on Ctrl-C.
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Stops the walk once passed, and sets `cancel` to say so.
+    pub deadline: Option<Instant>,
 }
 
 // ---------------------------------------------------------------------------
 // Matchers
 // ---------------------------------------------------------------------------
 
+/// A matcher that can say *where* in a file name it matched, so output can
+/// highlight the span instead of the whole path.
+pub trait SpanMatcher: Matcher {
+    /// Byte ranges of `name` that satisfied the match, or `None` if the name
+    /// does not match. An empty `Vec` means "matched, nothing to highlight".
+    fn find(&self, name: &str) -> Option<Vec<Range<usize>>>;
+
+    /// The kind every match has, when the matcher accepts only one.
+    fn kind(&self) -> Option<EntryKind> {
+        None
+    }
+}
+
+fn file_name(entry: &parex::Entry) -> &str {
+    entry
+        .path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+}
+
+/// The match test itself, run on every entry walked: one lowercase copy and
+/// a `contains`, nothing per character.
+fn contains_name(name: &str, pattern: &str, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        name.contains(pattern)
+    } else {
+        name.to_lowercase().contains(&pattern.to_lowercase())
+    }
+}
+
+/// The span to highlight for a name [`contains_name`] accepted. Only printed
+/// matches get here; when lowercasing changes a character's length and no
+/// span lines up, the name is still a match, just without a highlight.
+fn name_span(name: &str, pattern: &str, case_sensitive: bool) -> Option<Vec<Range<usize>>> {
+    if !contains_name(name, pattern, case_sensitive) {
+        return None;
+    }
+    Some(
+        find_substring(name, pattern, case_sensitive)
+            .into_iter()
+            .collect(),
+    )
+}
+
+/// First occurrence of `needle` in `hay`, compared char by char so the
+/// returned range always lands on `hay`'s own char boundaries.
+fn find_substring(hay: &str, needle: &str, case_sensitive: bool) -> Option<Range<usize>> {
+    if case_sensitive {
+        return hay.find(needle).map(|i| i..i + needle.len());
+    }
+    if needle.is_empty() {
+        return Some(0..0);
+    }
+    hay.char_indices().find_map(|(start, _)| {
+        let mut rest = hay[start..].char_indices();
+        for n in needle.chars() {
+            let (_, h) = rest.next()?;
+            if !h.to_lowercase().eq(n.to_lowercase()) {
+                return None;
+            }
+        }
+        let end = rest.next().map(|(i, _)| start + i).unwrap_or(hay.len());
+        Some(start..end)
+    })
+}
+
 /// Matches files by name substring (case-insensitive or sensitive).
 struct NameMatcher {
     pattern: String,
     case_sensitive: bool,
 }
 
+impl SpanMatcher for NameMatcher {
+    fn find(&self, name: &str) -> Option<Vec<Range<usize>>> {
+        name_span(name, &self.pattern, self.case_sensitive)
+    }
+}
+
 impl Matcher for NameMatcher {
     fn is_match(&self, entry: &parex::Entry) -> bool {
-        let name = entry
-            .path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("");
-        if self.case_sensitive {
-            name.contains(&self.pattern)
-        } else {
-            name.to_lowercase().contains(&self.pattern.to_lowercase())
-        }
+        contains_name(file_name(entry), &self.pattern, self.case_sensitive)
     }
 }
 
@@ -67,6 +210,19 @@ struct ExtMatcher {
     case_sensitive: bool,
 }
 
+impl SpanMatcher for ExtMatcher {
+    fn find(&self, name: &str) -> Option<Vec<Range<usize>>> {
+        let ext = Path::new(name).extension()?.to_str()?;
+        let hit = if self.case_sensitive {
+            ext == self.ext
+        } else {
+            ext.eq_ignore_ascii_case(&self.ext)
+        };
+        let span = name.len() - ext.len()..name.len();
+        hit.then(|| vec![span])
+    }
+}
+
 impl Matcher for ExtMatcher {
     fn is_match(&self, entry: &parex::Entry) -> bool {
         entry.path.extension().is_some_and(|e| {
@@ -82,9 +238,19 @@ impl Matcher for ExtMatcher {
 /// Matches everything — used for -a/--all-files.
 struct AllMatcher;
 
+impl SpanMatcher for AllMatcher {
+    fn find(&self, _name: &str) -> Option<Vec<Range<usize>>> {
+        Some(Vec::new())
+    }
+
+    fn kind(&self) -> Option<EntryKind> {
+        Some(EntryKind::File)
+    }
+}
+
 impl Matcher for AllMatcher {
     fn is_match(&self, entry: &parex::Entry) -> bool {
-        matches!(entry.kind, parex::EntryKind::File)
+        matches!(entry.kind, EntryKind::File)
     }
 }
 
@@ -94,96 +260,279 @@ struct DirMatcher {
     case_sensitive: bool,
 }
 
-impl Matcher for DirMatcher {
-    fn is_match(&self, entry: &parex::Entry) -> bool {
-        if !matches!(entry.kind, parex::EntryKind::Dir) {
-            return false;
-        }
+impl SpanMatcher for DirMatcher {
+    fn find(&self, name: &str) -> Option<Vec<Range<usize>>> {
         match &self.pattern {
-            None => true,
-            Some(pat) => {
-                let name = entry
-                    .path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("");
-                if self.case_sensitive {
-                    name.contains(pat)
-                } else {
-                    name.to_lowercase().contains(&pat.to_lowercase())
-                }
-            }
+            None => Some(Vec::new()),
+            Some(pat) => name_span(name, pat, self.case_sensitive),
         }
     }
+
+    fn kind(&self) -> Option<EntryKind> {
+        Some(EntryKind::Dir)
+    }
+}
+
+impl Matcher for DirMatcher {
+    fn is_match(&self, entry: &parex::Entry) -> bool {
+        matches!(entry.kind, EntryKind::Dir)
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|pat| contains_name(file_name(entry), pat, self.case_sensitive))
+    }
 }
 
 // ---------------------------------------------------------------------------
-// scan_dir — thin wrapper around parex::search()
+// Matcher selection
 // ---------------------------------------------------------------------------
 
-pub fn scan_dir(dir: &PathBuf, config: &Config) -> ScanResult {
-    let source = DirectorySource::new(dir)
+/// Picks the matcher for a search config. Shared so walkers outside the
+/// parex engine (e.g. `--top`) apply exactly the same rules as `scan_dir`.
+pub fn matcher_for(config: &Config) -> Arc<dyn SpanMatcher> {
+    if config.all {
+        Arc::new(AllMatcher)
+    } else if config.dirs_only {
+        Arc::new(DirMatcher {
+            pattern: config.pattern.clone(),
+            case_sensitive: config.case_sensitive,
+        })
+    } else if let Some(ext) = &config.extension {
+        Arc::new(ExtMatcher {
+            ext: ext.clone(),
+            case_sensitive: config.case_sensitive,
+        })
+    } else {
+        Arc::new(NameMatcher {
+            pattern: config.pattern.clone().unwrap_or_default(),
+            case_sensitive: config.case_sensitive,
+        })
+    }
+}
+
+/// Lets a shared matcher be handed to `SearchBuilder::with_matcher`.
+struct SharedMatcher(Arc<dyn SpanMatcher>);
+
+impl Matcher for SharedMatcher {
+    fn is_match(&self, entry: &parex::Entry) -> bool {
+        self.0.is_match(entry)
+    }
+}
+
+/// Whether a walk of the root would have pruned `rel` (a path relative to
+/// it): `--exclude` names skip directories, never plain files.
+pub fn is_excluded(rel: &Path, is_dir: bool, exclude: &[String]) -> bool {
+    let depth = rel.components().count();
+    rel.components().enumerate().any(|(i, c)| {
+        (i + 1 < depth || is_dir) && exclude.iter().any(|x| c.as_os_str() == x.as_str())
+    })
+}
+
+pub fn source_for(dir: &Path, config: &Config) -> DirectorySource {
+    DirectorySource::new(dir)
         .exclude(config.exclude.clone())
         .dirs_only(config.dirs_only)
-        .follow_links(false);
+        .follow_links(false)
+        .cancel(config.cancel.clone())
+        .deadline(config.deadline)
+}
 
+// ---------------------------------------------------------------------------
+// scan_dir — thin wrapper around parex::search()
+// ---------------------------------------------------------------------------
+
+/// Fails only if the search itself cannot run (e.g. zero threads); paths the
+/// walk could not read end up in `errors` instead.
+pub fn scan_dir(dir: &Path, config: &Config) -> Result<ScanResult, ParexError> {
     let mut builder = parex::search()
-        .source(source)
+        .source(source_for(dir, config))
         .threads(config.threads)
         .collect_paths(config.collect_paths)
-        .collect_errors(config.collect_errors);
+        .collect_errors(config.collect_errors)
+        .with_matcher(SharedMatcher(matcher_for(config)));
 
     if let Some(lim) = config.limit {
         builder = builder.limit(lim);
     }
 
-    // Wire up the right matcher
-    let result = if config.all {
-        builder.with_matcher(AllMatcher).run()
-    } else if config.dirs_only {
-        builder
-            .with_matcher(DirMatcher {
-                pattern: config.pattern.clone(),
-                case_sensitive: config.case_sensitive,
-            })
-            .run()
-    } else if let Some(ext) = &config.extension {
-        builder
-            .with_matcher(ExtMatcher {
-                ext: ext.clone(),
-                case_sensitive: config.case_sensitive,
-            })
-            .run()
-    } else {
-        builder
-            .with_matcher(NameMatcher {
-                pattern: config.pattern.clone().unwrap_or_default(),
-                case_sensitive: config.case_sensitive,
-            })
-            .run()
-    };
-
-    let result = result.expect("parex search failed");
-
-    // Print matches as we go — parex doesn't handle output, we do
-    if !config.quiet && !config.all {
-        for path in &result.paths {
-            let rel = path.strip_prefix(dir).unwrap_or(path);
-            let disp = if rel.as_os_str().is_empty() {
-                ".".to_string()
-            } else {
-                rel.to_string_lossy().into_owned()
-            };
-            println!("{}", colored::Colorize::bright_cyan(disp.as_str()));
-        }
-    }
+    let result = builder.run()?;
 
-    ScanResult {
+    Ok(ScanResult {
         matches: result.matches,
         files: result.stats.files,
         dirs: result.stats.dirs,
         duration: result.stats.duration,
         paths: result.paths,
         errors: result.errors,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// scan_roots — several roots at once within one thread budget
+// ---------------------------------------------------------------------------
+
+/// Scans every root with [`scan_dir`] at the same time, splitting
+/// `config.threads` between them (at least one each), so a slow root such as
+/// a network mount never holds up the others. A `config.limit` caps the
+/// combined matches: each finished scan claims what it found from the
+/// shared remainder. Results come back in the order of `roots`.
+pub fn scan_roots(roots: &[PathBuf], config: &Config) -> Vec<Result<ScanResult, ParexError>> {
+    let per_root = Config {
+        threads: (config.threads / roots.len().max(1)).max(1),
+        ..config.clone()
+    };
+    let left = config.limit.map(AtomicUsize::new);
+
+    std::thread::scope(|s| {
+        let scans: Vec<_> = roots
+            .iter()
+            .map(|root| {
+                s.spawn(|| {
+                    let mut result = scan_dir(root, &per_root)?;
+                    if let Some(left) = &left {
+                        let claimed = claim(left, result.matches);
+                        result.matches = claimed;
+                        result.paths.truncate(claimed);
+                    }
+                    Ok(result)
+                })
+            })
+            .collect();
+        scans
+            .into_iter()
+            .map(|scan| {
+                scan.join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// Takes up to `wanted` from `left`, returning how many it got.
+fn claim(left: &AtomicUsize, wanted: usize) -> usize {
+    let before = left
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+            Some(n.saturating_sub(wanted))
+        })
+        .unwrap_or_else(|n| n);
+    before.min(wanted)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tempdir::TempDir;
+
+    #[test]
+    fn substring_span_is_case_insensitive() {
+        assert_eq!(
+            find_substring("My_Invoice.pdf", "invoice", false),
+            Some(3..10)
+        );
+        assert_eq!(find_substring("My_Invoice.pdf", "invoice", true), None);
+    }
+
+    #[test]
+    fn names_whose_lowercase_changes_length_still_match() {
+        // 'İ' lowercases to "i" plus a combining dot: two chars for one
+        let (name, pattern) = ("İstanbul.txt", "i\u{307}st");
+        assert!(contains_name(name, pattern, false));
+        assert_eq!(name_span(name, pattern, false), Some(Vec::new()));
+        assert_eq!(name_span(name, "paris", false), None);
+    }
+
+    #[test]
+    fn substring_span_respects_multibyte_names() {
+        let name = "résumé_final.txt";
+        let span = find_substring(name, "FINAL", false).unwrap();
+        assert_eq!(&name[span], "final");
+    }
+
+    #[test]
+    fn excludes_prune_directories_only() {
+        let exclude = ["target".to_string()];
+        assert!(is_excluded(Path::new("a/target/x.o"), false, &exclude));
+        assert!(is_excluded(Path::new("a/target"), true, &exclude));
+        assert!(!is_excluded(Path::new("a/target"), false, &exclude));
+    }
+
+    #[test]
+    fn io_errors_are_categorized_by_kind() {
+        let io = |kind| ParexError::Io {
+            path: PathBuf::from("/x"),
+            source: io::Error::from(kind),
+        };
+        assert_eq!(
+            ErrorCategory::of(&io(io::ErrorKind::PermissionDenied)),
+            ErrorCategory::PermissionDenied
+        );
+        assert_eq!(
+            ErrorCategory::of(&io(io::ErrorKind::NotFound)),
+            ErrorCategory::Vanished
+        );
+        assert_eq!(
+            ErrorCategory::of(&io(io::ErrorKind::TimedOut)),
+            ErrorCategory::Io
+        );
+        assert_eq!(
+            ErrorCategory::of(&ParexError::SymlinkLoop("/x".into())),
+            ErrorCategory::SymlinkLoop
+        );
+        assert_eq!(error_reason(&io(io::ErrorKind::TimedOut)), "timed out");
+    }
+
+    #[test]
+    fn scan_roots_keeps_root_order() {
+        let base = TempDir::new("roots");
+        let roots: Vec<PathBuf> = ["one", "two", "three"]
+            .iter()
+            .map(|r| base.join(r))
+            .collect();
+        for (n, root) in roots.iter().enumerate() {
+            std::fs::create_dir_all(root).unwrap();
+            for i in 0..=n {
+                std::fs::write(root.join(format!("f{i}.txt")), "").unwrap();
+            }
+        }
+        let config = Config {
+            all: true,
+            threads: 2,
+            collect_paths: true,
+            ..Config::default()
+        };
+        let matches: Vec<usize> = scan_roots(&roots, &config)
+            .into_iter()
+            .map(|r| r.unwrap().matches)
+            .collect();
+
+        assert_eq!(matches, [1, 2, 3]);
+
+        let limited = Config {
+            limit: Some(2),
+            ..config
+        };
+        let results: Vec<ScanResult> = scan_roots(&roots, &limited)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        // which roots the two matches come from depends on which scan
+        // finishes first
+        assert_eq!(results.iter().map(|r| r.matches).sum::<usize>(), 2);
+        assert!(results.iter().all(|r| r.paths.len() == r.matches));
+    }
+
+    #[test]
+    fn ext_matcher_highlights_extension() {
+        let m = ExtMatcher {
+            ext: "rs".into(),
+            case_sensitive: false,
+        };
+        let span = 5..7;
+        assert_eq!(m.find("main.RS"), Some(vec![span]));
+        assert_eq!(m.find("main.rsx"), None);
     }
 }
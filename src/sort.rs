@@ -0,0 +1,136 @@
+use anyhow::{Result, bail};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+// ---------------------------------------------------------------------------
+// Sort spec — parsed from --sort KEY[-asc|-desc]
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Path,
+    Size,
+    Mtime,
+    Depth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortSpec {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl FromStr for SortSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (key, descending) = match s.rsplit_once('-') {
+            Some((key, "desc")) => (key, true),
+            Some((key, "asc")) => (key, false),
+            _ => (s, false),
+        };
+        let key = match key {
+            "name" => SortKey::Name,
+            "path" => SortKey::Path,
+            "size" => SortKey::Size,
+            "mtime" => SortKey::Mtime,
+            "depth" => SortKey::Depth,
+            _ => bail!(
+                "Invalid --sort value: {:?}. Expected name, path, size, mtime or depth (optionally with -asc/-desc).",
+                s
+            ),
+        };
+        Ok(SortSpec { key, descending })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// sort_paths — runs after collection, stats only for keys that need it
+// ---------------------------------------------------------------------------
+
+pub fn sort_paths(paths: &mut Vec<PathBuf>, spec: SortSpec, reverse: bool) {
+    let descending = spec.descending != reverse;
+
+    match spec.key {
+        SortKey::Name => paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()).then(a.cmp(b))),
+        SortKey::Path => paths.sort(),
+        SortKey::Depth => paths.sort_by(|a, b| {
+            a.components()
+                .count()
+                .cmp(&b.components().count())
+                .then(a.cmp(b))
+        }),
+        SortKey::Size => sort_by_metadata(paths, |m| m.len()),
+        SortKey::Mtime => {
+            sort_by_metadata(paths, |m| m.modified().unwrap_or(SystemTime::UNIX_EPOCH))
+        }
+    }
+
+    if descending {
+        paths.reverse();
+    }
+}
+
+/// Stats each path once, sorts by the extracted key, ties broken by path.
+/// Entries whose metadata cannot be read sort first.
+fn sort_by_metadata<K, F>(paths: &mut Vec<PathBuf>, key: F)
+where
+    K: Ord,
+    F: Fn(&std::fs::Metadata) -> K,
+{
+    let mut keyed: Vec<(Option<K>, PathBuf)> = std::mem::take(paths)
+        .into_iter()
+        .map(|p| (std::fs::symlink_metadata(&p).ok().map(|m| key(&m)), p))
+        .collect();
+    keyed.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+    *paths = keyed.into_iter().map(|(_, p)| p).collect();
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &[&str]) -> Vec<PathBuf> {
+        list.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn parses_plain_key_as_ascending() {
+        let spec: SortSpec = "name".parse().unwrap();
+        assert_eq!(spec.key, SortKey::Name);
+        assert!(!spec.descending);
+    }
+
+    #[test]
+    fn parses_desc_suffix() {
+        let spec: SortSpec = "mtime-desc".parse().unwrap();
+        assert_eq!(spec.key, SortKey::Mtime);
+        assert!(spec.descending);
+    }
+
+    #[test]
+    fn rejects_unknown_key() {
+        assert!("colour".parse::<SortSpec>().is_err());
+        assert!("size-sideways".parse::<SortSpec>().is_err());
+    }
+
+    #[test]
+    fn sorts_by_name_then_path() {
+        let mut p = paths(&["/z/b.txt", "/a/c.txt", "/y/b.txt"]);
+        sort_paths(&mut p, "name".parse().unwrap(), false);
+        assert_eq!(p, paths(&["/y/b.txt", "/z/b.txt", "/a/c.txt"]));
+    }
+
+    #[test]
+    fn reverse_flips_descending_spec() {
+        let mut p = paths(&["/a/b/c", "/a", "/a/b"]);
+        sort_paths(&mut p, "depth-desc".parse().unwrap(), true);
+        assert_eq!(p, paths(&["/a", "/a/b", "/a/b/c"]));
+    }
+}
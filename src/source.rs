@@ -1,45 +1,191 @@
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
 use parawalk::{EntryKind as WalkKind, EntryRef, WalkConfig as ParaConfig};
-use parex::Source;
 use parex::engine::WalkConfig;
 use parex::{Entry, EntryKind, ParexError};
+use parex::{Matcher, Source};
 
 const BATCH_SIZE: usize = 128;
 
+/// How often a caller waiting on the walk looks at the cancel flag, so a
+/// stop is seen even while every walker thread is stuck in a slow read.
+const POLL: Duration = Duration::from_millis(50);
+
+type Visit = Result<Entry, ParexError>;
+
 // ---------------------------------------------------------------------------
-// BatchSender — flushes remaining entries on drop
+// Stop — parawalk cannot be interrupted, so once the cancel flag is set (by
+// the caller, or here when the deadline passes) every callback returns at
+// once without allocating, and the caller stops waiting. The walker threads
+// run out the tree in the background with nothing left to report.
 // ---------------------------------------------------------------------------
 
-struct BatchSender {
-    tx: mpsc::Sender<Vec<Entry>>,
-    batch: Vec<Entry>,
+#[derive(Clone)]
+struct Stop {
+    cancel: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    /// Set once the receiver is gone; stops the callbacks, not the caller.
+    hung_up: Arc<AtomicBool>,
 }
 
-impl BatchSender {
-    fn new(tx: mpsc::Sender<Vec<Entry>>) -> Self {
-        Self {
-            tx,
-            batch: Vec::with_capacity(BATCH_SIZE),
+impl Stop {
+    fn stopped(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed) || self.hung_up.load(Ordering::Relaxed)
+    }
+
+    /// Checked once per directory, where reading the clock is cheap next to
+    /// the `read_dir` that follows.
+    fn out_of_time(&self) -> bool {
+        let expired = self.deadline.is_some_and(|at| Instant::now() >= at);
+        if expired {
+            self.cancel.store(true, Ordering::Relaxed);
         }
+        expired
     }
 
-    fn push(&mut self, entry: Entry) {
-        self.batch.push(entry);
-        if self.batch.len() >= BATCH_SIZE {
-            let _ = self.tx.send(std::mem::take(&mut self.batch));
-            self.batch = Vec::with_capacity(BATCH_SIZE);
+    /// The walk's next message; `None` once it is over or stopped.
+    fn recv<T>(&self, rx: &mpsc::Receiver<T>) -> Option<T> {
+        loop {
+            if self.stopped() || self.out_of_time() {
+                return None;
+            }
+            let wait = match self.deadline {
+                Some(at) => POLL.min(at.saturating_duration_since(Instant::now())),
+                None => POLL,
+            };
+            match rx.recv_timeout(wait) {
+                Ok(msg) => return Some(msg),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
         }
     }
 }
 
-impl Drop for BatchSender {
-    fn drop(&mut self) {
-        if !self.batch.is_empty() {
-            let _ = self.tx.send(std::mem::take(&mut self.batch));
+// ---------------------------------------------------------------------------
+// Slots — what each walker thread holds but has not handed over yet, so a
+// stopped walk can still collect it while the thread is busy
+// ---------------------------------------------------------------------------
+
+struct Slots<T>(Arc<Mutex<Vec<Arc<Mutex<T>>>>>);
+
+impl<T> Clone for Slots<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<T: Default> Slots<T> {
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(Vec::new())))
+    }
+
+    /// A slot for one walker thread; uncontended until the walk is stopped.
+    fn add(&self, value: T) -> Arc<Mutex<T>> {
+        let slot = Arc::new(Mutex::new(value));
+        lock(&self.0).push(Arc::clone(&slot));
+        slot
+    }
+
+    /// Empties every slot. A thread in the middle of an entry finishes it
+    /// first.
+    fn take_all(&self) -> Vec<T> {
+        lock(&self.0)
+            .iter()
+            .map(|slot| std::mem::take(&mut *lock(slot)))
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// FoldPart — one walker thread's accumulator
+// ---------------------------------------------------------------------------
+
+struct FoldPart<T> {
+    acc: T,
+    files: usize,
+    dirs: usize,
+    errors: Vec<ParexError>,
+}
+
+/// Per-thread accumulators from [`DirectorySource::fold`] plus walk totals.
+pub struct Folded<T> {
+    pub parts: Vec<T>,
+    pub files: usize,
+    pub dirs: usize,
+    pub duration: Duration,
+    /// Paths the walk could not read, in no particular order.
+    pub errors: Vec<ParexError>,
+}
+
+// ---------------------------------------------------------------------------
+// Pruner — parawalk descends into every directory, even ones the pre-filter
+// rejects, so entries below an excluded directory are dropped here. It also
+// stands in for the errors parawalk swallows: each directory parawalk is
+// about to read is opened once more, and a failure becomes a `ParexError`.
+// ---------------------------------------------------------------------------
+
+#[derive(Clone)]
+struct Pruner {
+    root: PathBuf,
+    exclude: Arc<[String]>,
+    max_depth: Option<usize>,
+    stop: Stop,
+}
+
+impl Pruner {
+    fn keeps(&self, path: &Path) -> bool {
+        if self.exclude.is_empty() {
+            return true;
         }
+        let Some(parent) = path.strip_prefix(&self.root).ok().and_then(Path::parent) else {
+            return true;
+        };
+        !parent
+            .components()
+            .any(|c| self.exclude.iter().any(|x| c.as_os_str() == x.as_str()))
+    }
+
+    /// Whether to stop, checking the deadline on each directory.
+    fn stopping(&self, walked: &parawalk::Entry) -> bool {
+        self.stop.stopped() || (walked.kind == WalkKind::Dir && self.stop.out_of_time())
     }
+
+    /// Reports one parawalk entry unless it is pruned; a directory parawalk
+    /// will descend into is reported after an `Err` if it cannot be read.
+    fn visit(&self, walked: parawalk::Entry, mut report: impl FnMut(Visit)) {
+        if !self.keeps(&walked.path) {
+            return;
+        }
+        let descends = self.max_depth.is_none_or(|max| walked.depth < max);
+        if walked.kind == WalkKind::Dir
+            && descends
+            && let Some(err) = unreadable(&walked.path)
+        {
+            report(Err(err));
+        }
+        if let Some(entry) = to_entry(walked) {
+            report(Ok(entry));
+        }
+    }
+}
+
+/// Opens `dir` the way parawalk will, keeping the OS error so `-W -v` can
+/// show it; `ErrorCategory` groups it.
+fn unreadable(dir: &Path) -> Option<ParexError> {
+    let source = std::fs::read_dir(dir).err()?;
+    Some(ParexError::Io {
+        path: dir.to_path_buf(),
+        source,
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -51,6 +197,8 @@ pub struct DirectorySource {
     pub exclude: Vec<String>,
     pub dirs_only: bool,
     pub follow_links: bool,
+    pub cancel: Option<Arc<AtomicBool>>,
+    pub deadline: Option<Instant>,
 }
 
 impl DirectorySource {
@@ -60,6 +208,8 @@ impl DirectorySource {
             exclude: vec![],
             dirs_only: false,
             follow_links: false,
+            cancel: None,
+            deadline: None,
         }
     }
     pub fn exclude(mut self, dirs: Vec<String>) -> Self {
@@ -74,65 +224,279 @@ impl DirectorySource {
         self.follow_links = yes;
         self
     }
+    /// Stop reporting entries once `flag` is set; what was found so far is
+    /// kept.
+    pub fn cancel(mut self, flag: Option<Arc<AtomicBool>>) -> Self {
+        self.cancel = flag;
+        self
+    }
+    /// Stop reporting entries at `at`, and set the [`cancel`](Self::cancel)
+    /// flag so the caller can tell the walk was cut short.
+    pub fn deadline(mut self, at: Option<Instant>) -> Self {
+        self.deadline = at;
+        self
+    }
 }
 
-impl Source for DirectorySource {
-    fn walk(&self, config: &WalkConfig) -> Box<dyn Iterator<Item = Result<Entry, ParexError>>> {
-        let walk_config = ParaConfig {
-            threads: config.threads,
-            max_depth: config.max_depth,
+impl DirectorySource {
+    fn para_config(&self, threads: usize, max_depth: Option<usize>) -> ParaConfig {
+        ParaConfig {
+            threads,
+            max_depth,
             follow_links: self.follow_links,
+        }
+    }
+
+    fn stop(&self) -> Stop {
+        let stop = Stop {
+            cancel: self.cancel.clone().unwrap_or_default(),
+            deadline: self.deadline,
+            hung_up: Arc::default(),
         };
+        // a deadline that has already passed stops the walk before it starts
+        stop.out_of_time();
+        stop
+    }
 
-        let root = self.root.clone();
+    fn pruner(&self, max_depth: Option<usize>, stop: &Stop) -> Pruner {
+        Pruner {
+            root: self.root.clone(),
+            exclude: self.exclude.clone().into(),
+            max_depth,
+            stop: stop.clone(),
+        }
+    }
+
+    /// Cheap name/kind filter run by parawalk before any path is allocated.
+    /// Once stopped it rejects everything, so the rest of the walk only
+    /// reads directories.
+    fn pre_filter(&self, stop: &Stop) -> impl Fn(&EntryRef<'_>) -> bool + Send + Sync + 'static {
         let exclude = self.exclude.clone();
         let dirs_only = self.dirs_only;
+        let stop = stop.clone();
+        move |entry: &EntryRef<'_>| {
+            if stop.stopped() {
+                return false;
+            }
+            let name = entry.name.to_string_lossy();
+            if entry.kind == WalkKind::Dir && exclude.contains(&name.to_string()) {
+                return false;
+            }
+            if dirs_only && entry.kind == WalkKind::File {
+                return false;
+            }
+            true
+        }
+    }
 
-        let (tx, rx) = mpsc::channel::<Vec<Entry>>();
-        let tx_visitor = tx.clone();
+    /// Walks the tree and folds matching entries into one accumulator per
+    /// walker thread. Nothing crosses a channel, so reports like `--top`
+    /// never materialize the full match list. Returns once the walk is over
+    /// or stopped.
+    pub fn fold<T, I, F>(
+        &self,
+        threads: usize,
+        matcher: Arc<dyn Matcher>,
+        init: I,
+        fold: F,
+    ) -> Folded<T>
+    where
+        T: Send + 'static,
+        I: Fn() -> T + Send + Sync + 'static,
+        F: Fn(&mut T, Entry) + Send + Sync + 'static,
+    {
+        let start = Instant::now();
+        let stop = self.stop();
+        let pruner = self.pruner(None, &stop);
+        let root_error = unreadable(&self.root);
+        let slots = Slots::<Option<FoldPart<T>>>::new();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let fold = Arc::new(fold);
 
+        let root = self.root.clone();
+        let walk_config = self.para_config(threads, None);
+        let pre_filter = self.pre_filter(&stop);
+        let walk_slots = slots.clone();
         std::thread::spawn(move || {
-            parawalk::walk(
-                root,
-                walk_config,
-                Some(move |entry: &EntryRef<'_>| {
-                    let name = entry.name.to_string_lossy();
-                    if entry.kind == WalkKind::Dir && exclude.contains(&name.to_string()) {
-                        return false;
-                    }
-                    if dirs_only && entry.kind == WalkKind::File {
-                        return false;
+            parawalk::walk(root, walk_config, Some(pre_filter), move || {
+                let slot = walk_slots.add(Some(FoldPart {
+                    acc: init(),
+                    files: 0,
+                    dirs: 0,
+                    errors: Vec::new(),
+                }));
+                let matcher = Arc::clone(&matcher);
+                let fold = Arc::clone(&fold);
+                let pruner = pruner.clone();
+
+                move |walked: parawalk::Entry| {
+                    if pruner.stopping(&walked) {
+                        return;
                     }
-                    true
-                }),
-                move || {
-                    // Each thread gets its own BatchSender — no locking needed.
-                    // Drop impl ensures partial batches are flushed when walk ends.
-                    let mut sender = BatchSender::new(tx_visitor.clone());
-
-                    move |walked: parawalk::Entry| {
-                        let kind = match walked.kind {
-                            WalkKind::Dir => EntryKind::Dir,
-                            WalkKind::Symlink => EntryKind::Symlink,
-                            WalkKind::File => EntryKind::File,
-                            WalkKind::Other => return,
+                    let mut part = lock(&slot);
+                    let Some(part) = part.as_mut() else {
+                        return;
+                    };
+                    pruner.visit(walked, |visit| {
+                        let entry = match visit {
+                            Ok(entry) => entry,
+                            Err(err) => return part.errors.push(err),
                         };
+                        match entry.kind {
+                            EntryKind::Dir => part.dirs += 1,
+                            EntryKind::File => part.files += 1,
+                            _ => {}
+                        }
+                        if matcher.is_match(&entry) {
+                            fold(&mut part.acc, entry);
+                        }
+                    });
+                }
+            });
+            drop(done_tx);
+        });
+        // only ever disconnects: the walk is over, or `recv` gives up once
+        // stopped
+        let _ = stop.recv(&done_rx);
+
+        let mut folded = Folded {
+            parts: Vec::new(),
+            files: 0,
+            dirs: 0,
+            duration: Duration::ZERO,
+            errors: root_error.into_iter().collect(),
+        };
+        for part in slots.take_all().into_iter().flatten() {
+            folded.files += part.files;
+            folded.dirs += part.dirs;
+            folded.errors.extend(part.errors);
+            folded.parts.push(part.acc);
+        }
+        folded.duration = start.elapsed();
+        folded
+    }
+}
+
+impl Source for DirectorySource {
+    fn walk(&self, config: &WalkConfig) -> Box<dyn Iterator<Item = Result<Entry, ParexError>>> {
+        Box::new(self.entries(config.threads, config.max_depth))
+    }
+}
+
+impl DirectorySource {
+    /// Streams entries in batches while the walk is still running, in no
+    /// particular order, with an `Err` for each directory that could not be
+    /// read. The stream ends early once stopped, with what the walker
+    /// threads had found; dropping it stops them reporting too.
+    pub fn entries(
+        &self,
+        threads: usize,
+        max_depth: Option<usize>,
+    ) -> impl Iterator<Item = Result<Entry, ParexError>> + Send + 'static {
+        let stop = self.stop();
+        let walk_config = self.para_config(threads, max_depth);
+        let root = self.root.clone();
+        let pre_filter = self.pre_filter(&stop);
+        let pruner = self.pruner(max_depth, &stop);
+        let slots = Slots::<Vec<Visit>>::new();
 
-                        sender.push(Entry {
-                            path: walked.path,
-                            kind,
-                            depth: walked.depth,
-                            metadata: None,
-                        });
+        let (tx, rx) = mpsc::channel::<Vec<Visit>>();
+        if let Some(err) = unreadable(&root) {
+            let _ = tx.send(vec![Err(err)]);
+        }
+
+        let walk_slots = slots.clone();
+        let hung_up = Arc::clone(&stop.hung_up);
+        std::thread::spawn(move || {
+            let tx_visitor = tx.clone();
+            let thread_slots = walk_slots.clone();
+            parawalk::walk(root, walk_config, Some(pre_filter), move || {
+                // Each thread fills its own slot and sends it once full; the
+                // lock is only ever contended when a stopped walk empties it.
+                let slot = thread_slots.add(Vec::with_capacity(BATCH_SIZE));
+                let tx = tx_visitor.clone();
+                let hung_up = Arc::clone(&hung_up);
+                let pruner = pruner.clone();
+
+                move |walked: parawalk::Entry| {
+                    if pruner.stopping(&walked) {
+                        return;
                     }
-                },
-            );
+                    let mut batch = lock(&slot);
+                    pruner.visit(walked, |visit| batch.push(visit));
+                    if batch.len() >= BATCH_SIZE {
+                        let full = std::mem::replace(&mut *batch, Vec::with_capacity(BATCH_SIZE));
+                        if tx.send(full).is_err() {
+                            hung_up.store(true, Ordering::Relaxed);
+                        }
+                    }
+                }
+            });
+            for batch in walk_slots.take_all() {
+                let _ = tx.send(batch);
+            }
         });
 
-        Box::new(rx.into_iter().flatten().map(Ok))
+        Entries {
+            rx,
+            stop,
+            slots,
+            batch: Vec::new().into_iter(),
+            done: false,
+        }
     }
 }
 
+/// The stream behind [`DirectorySource::entries`].
+struct Entries {
+    rx: mpsc::Receiver<Vec<Visit>>,
+    stop: Stop,
+    slots: Slots<Vec<Visit>>,
+    batch: std::vec::IntoIter<Visit>,
+    done: bool,
+}
+
+impl Iterator for Entries {
+    type Item = Visit;
+
+    fn next(&mut self) -> Option<Visit> {
+        loop {
+            if let Some(visit) = self.batch.next() {
+                return Some(visit);
+            }
+            if self.done {
+                return None;
+            }
+            match self.stop.recv(&self.rx) {
+                Some(batch) => self.batch = batch.into_iter(),
+                None => {
+                    // over or stopped: what was sent, then what the threads
+                    // still hold (nothing, if the walk ran to the end)
+                    self.done = true;
+                    let mut rest: Vec<Visit> = self.rx.try_iter().flatten().collect();
+                    rest.extend(self.slots.take_all().into_iter().flatten());
+                    self.batch = rest.into_iter();
+                }
+            }
+        }
+    }
+}
+
+fn to_entry(walked: parawalk::Entry) -> Option<Entry> {
+    let kind = match walked.kind {
+        WalkKind::Dir => EntryKind::Dir,
+        WalkKind::Symlink => EntryKind::Symlink,
+        WalkKind::File => EntryKind::File,
+        WalkKind::Other => return None,
+    };
+    Some(Entry {
+        path: walked.path,
+        kind,
+        depth: walked.depth,
+        metadata: None,
+    })
+}
+
 #[cfg(windows)]
 pub fn get_all_drives() -> Vec<PathBuf> {
     ('A'..='Z')
@@ -140,3 +504,152 @@ pub fn get_all_drives() -> Vec<PathBuf> {
         .filter(|p| p.exists())
         .collect()
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tempdir::TempDir;
+
+    fn collect(
+        source: &DirectorySource,
+        max_depth: Option<usize>,
+    ) -> (Vec<PathBuf>, Vec<ParexError>) {
+        let (mut paths, mut errors) = (Vec::new(), Vec::new());
+        for visit in source.entries(3, max_depth) {
+            match visit {
+                Ok(e) => paths.push(e.path),
+                Err(e) => errors.push(e),
+            }
+        }
+        paths.sort();
+        (paths, errors)
+    }
+
+    /// `width` directories per level, `depth` levels, a file in each: wide
+    /// and deep enough that every thread steals work from the others.
+    fn busy_tree(width: usize, depth: usize) -> (TempDir, usize) {
+        fn fill(dir: &Path, width: usize, depth: usize) -> usize {
+            std::fs::write(dir.join("f.txt"), "").unwrap();
+            if depth == 0 {
+                return 1;
+            }
+            let mut made = 1;
+            for i in 0..width {
+                let sub = dir.join(format!("d{}", i));
+                std::fs::create_dir(&sub).unwrap();
+                made += 1 + fill(&sub, width, depth - 1);
+            }
+            made
+        }
+        let root = TempDir::new("busy");
+        let entries = fill(&root, width, depth);
+        (root, entries)
+    }
+
+    fn count_all() -> Arc<dyn Matcher> {
+        struct All;
+        impl Matcher for All {
+            fn is_match(&self, _entry: &Entry) -> bool {
+                true
+            }
+        }
+        Arc::new(All)
+    }
+
+    #[test]
+    fn walks_everything_but_excluded_subtrees() {
+        let root = TempDir::new("source");
+        for dir in ["a/b/c", "skip/inner", "d"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("a/b/c/f.txt"), "").unwrap();
+        std::fs::write(root.join("skip/inner/g.txt"), "").unwrap();
+        let source = DirectorySource::new(&*root).exclude(vec!["skip".into()]);
+
+        let (paths, errors) = collect(&source, None);
+        let rel: Vec<&Path> = paths
+            .iter()
+            .map(|p| p.strip_prefix(&*root).unwrap())
+            .collect();
+        assert_eq!(
+            rel,
+            ["a", "a/b", "a/b/c", "a/b/c/f.txt", "d"].map(Path::new)
+        );
+        assert!(errors.is_empty());
+        assert_eq!(collect(&source, Some(2)).0.len(), 3);
+    }
+
+    #[test]
+    fn unreadable_roots_are_reported() {
+        let (paths, errors) = collect(&DirectorySource::new("/nonexistent/ldx-source"), None);
+        assert!(paths.is_empty());
+        assert!(matches!(
+            errors.as_slice(),
+            [ParexError::Io { source, .. }] if source.kind() == std::io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn many_threads_report_each_entry_once() {
+        let (root, total) = busy_tree(6, 3);
+        let source = DirectorySource::new(&*root);
+        for threads in [1, 4, 16] {
+            let mut paths: Vec<PathBuf> = source
+                .entries(threads, None)
+                .map(|visit| visit.unwrap().path)
+                .collect();
+            paths.sort();
+            paths.dedup();
+            assert_eq!(paths.len(), total, "entries with {} threads", threads);
+
+            let folded = source.fold(threads, count_all(), || 0usize, |n, _| *n += 1);
+            assert_eq!(folded.parts.iter().sum::<usize>(), total);
+            assert_eq!(folded.files + folded.dirs, total);
+        }
+    }
+
+    #[test]
+    fn cancelled_walks_report_nothing() {
+        let (root, _) = busy_tree(3, 2);
+        let source = DirectorySource::new(&*root).cancel(Some(Arc::new(AtomicBool::new(true))));
+        assert_eq!(source.entries(4, None).count(), 0);
+        let folded = source.fold(4, count_all(), || 0usize, |n, _| *n += 1);
+        assert_eq!(folded.parts.iter().sum::<usize>(), 0);
+    }
+
+    #[test]
+    fn a_passed_deadline_stops_the_walk_and_sets_cancel() {
+        let (root, _) = busy_tree(3, 2);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let source = DirectorySource::new(&*root)
+            .cancel(Some(Arc::clone(&cancel)))
+            .deadline(Some(Instant::now()));
+        assert_eq!(source.entries(4, None).count(), 0);
+        assert!(cancel.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn a_walk_stopped_midway_keeps_what_it_found() {
+        let (root, total) = busy_tree(6, 3);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let source = DirectorySource::new(&*root).cancel(Some(Arc::clone(&cancel)));
+        let flag = Arc::clone(&cancel);
+        let folded = source.fold(
+            4,
+            count_all(),
+            || 0usize,
+            move |n, _| {
+                *n += 1;
+                if *n == 50 {
+                    flag.store(true, Ordering::Relaxed);
+                }
+            },
+        );
+        let found = folded.parts.iter().sum::<usize>();
+        assert!((50..total).contains(&found), "found {} of {}", found, total);
+    }
+}
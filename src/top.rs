@@ -0,0 +1,226 @@
+use anyhow::{Result, bail};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use parex::EntryKind;
+
+use crate::search::{Config, ScanResult, matcher_for, source_for};
+
+// ---------------------------------------------------------------------------
+// Ranking key — parsed from --by size|mtime
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RankKey {
+    #[default]
+    Size,
+    Mtime,
+}
+
+impl FromStr for RankKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "size" => Ok(RankKey::Size),
+            "mtime" => Ok(RankKey::Mtime),
+            _ => bail!("Invalid --by value: {:?}. Expected size or mtime.", s),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Top-N result
+// ---------------------------------------------------------------------------
+
+pub struct TopEntry {
+    pub path: PathBuf,
+    pub kind: EntryKind,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+pub struct TopResult {
+    /// Winners, best first.
+    pub entries: Vec<TopEntry>,
+    /// Totals for the summary and stats lines. `matches` counts every
+    /// candidate, not just the winners; `paths` is left empty.
+    pub scan: ScanResult,
+}
+
+// ---------------------------------------------------------------------------
+// Bounded min-heap — keeps the N largest keys seen so far
+// ---------------------------------------------------------------------------
+
+struct Ranked {
+    key: u128,
+    entry: TopEntry,
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.entry.path == other.entry.path
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Ties prefer the lexically smaller path so output is stable.
+        self.key
+            .cmp(&other.key)
+            .then_with(|| other.entry.path.cmp(&self.entry.path))
+    }
+}
+
+fn push_bounded(heap: &mut BinaryHeap<Reverse<Ranked>>, n: usize, ranked: Ranked) {
+    if heap.len() < n {
+        heap.push(Reverse(ranked));
+    } else if heap.peek().is_some_and(|Reverse(min)| ranked > *min) {
+        heap.pop();
+        heap.push(Reverse(ranked));
+    }
+}
+
+fn rank(key: RankKey, meta: &std::fs::Metadata) -> (u128, u64, SystemTime) {
+    let size = meta.len();
+    let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let key = match key {
+        RankKey::Size => size as u128,
+        RankKey::Mtime => modified
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0),
+    };
+    (key, size, modified)
+}
+
+// ---------------------------------------------------------------------------
+// scan_top — one heap per walker thread, merged at the end
+// ---------------------------------------------------------------------------
+
+pub fn scan_top(dir: &Path, config: &Config, n: usize, key: RankKey) -> TopResult {
+    let dirs_only = config.dirs_only;
+
+    let folded = source_for(dir, config).fold(
+        config.threads,
+        matcher_for(config),
+        || (BinaryHeap::<Reverse<Ranked>>::new(), 0usize),
+        move |(heap, matched), entry| {
+            // Directory sizes are inode sizes, not contents — only rank them for -D.
+            if matches!(entry.kind, EntryKind::Dir) && !dirs_only {
+                return;
+            }
+            *matched += 1;
+            let Ok(meta) = std::fs::symlink_metadata(&entry.path) else {
+                return;
+            };
+            let (key, size, modified) = rank(key, &meta);
+            push_bounded(
+                heap,
+                n,
+                Ranked {
+                    key,
+                    entry: TopEntry {
+                        path: entry.path,
+                        kind: entry.kind,
+                        size,
+                        modified,
+                    },
+                },
+            );
+        },
+    );
+
+    let mut merged = BinaryHeap::with_capacity(n + 1);
+    let mut matches = 0usize;
+    for (heap, matched) in folded.parts {
+        matches += matched;
+        for Reverse(ranked) in heap {
+            push_bounded(&mut merged, n, ranked);
+        }
+    }
+
+    let entries: Vec<TopEntry> = merged
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(ranked)| ranked.entry)
+        .collect();
+
+    TopResult {
+        scan: ScanResult {
+            matches,
+            files: folded.files,
+            dirs: folded.dirs,
+            duration: folded.duration,
+            paths: Vec::new(),
+            errors: folded.errors,
+        },
+        entries,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(key: u128, path: &str) -> Ranked {
+        Ranked {
+            key,
+            entry: TopEntry {
+                path: path.into(),
+                kind: EntryKind::File,
+                size: key as u64,
+                modified: SystemTime::UNIX_EPOCH,
+            },
+        }
+    }
+
+    #[test]
+    fn bounded_heap_keeps_largest_keys() {
+        let mut heap = BinaryHeap::new();
+        for (k, p) in [(5, "e"), (1, "a"), (9, "i"), (3, "c"), (7, "g")] {
+            push_bounded(&mut heap, 3, ranked(k, p));
+        }
+        let keys: Vec<u128> = heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(r)| r.key)
+            .collect();
+        assert_eq!(keys, vec![9, 7, 5]);
+    }
+
+    #[test]
+    fn ties_prefer_smaller_path() {
+        let mut heap = BinaryHeap::new();
+        for p in ["c", "a", "b"] {
+            push_bounded(&mut heap, 2, ranked(1, p));
+        }
+        let paths: Vec<PathBuf> = heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(r)| r.entry.path)
+            .collect();
+        assert_eq!(paths, vec![PathBuf::from("a"), PathBuf::from("b")]);
+    }
+
+    #[test]
+    fn parses_rank_key() {
+        assert_eq!("mtime".parse::<RankKey>().unwrap(), RankKey::Mtime);
+        assert!("depth".parse::<RankKey>().is_err());
+    }
+}
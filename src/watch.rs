@@ -0,0 +1,202 @@
+use anyhow::{Context, Result};
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc;
+
+use parex::{Entry, EntryKind};
+
+use crate::cli::display::{display_path, paint_match};
+use crate::cli::flags::ParsedFlags;
+use crate::cli::theme::{hyperlink, success, warning};
+use crate::search::{Config, SpanMatcher, is_excluded, matcher_for, scan_dir};
+
+// ---------------------------------------------------------------------------
+// --watch — one non-recursive watch per directory the search would walk, so
+// excluded trees like node_modules never cost a watch (or an event)
+// ---------------------------------------------------------------------------
+
+struct Watch<'a> {
+    root: &'a Path,
+    config: &'a Config,
+    f: &'a ParsedFlags,
+    matcher: Arc<dyn SpanMatcher>,
+    watcher: RecommendedWatcher,
+    /// Paths currently matching, so each change prints exactly once.
+    matching: BTreeSet<PathBuf>,
+    unwatched: usize,
+}
+
+/// Matches below `dir`; a scan that cannot run just finds nothing new.
+fn scan_paths(dir: &Path, config: &Config) -> Vec<PathBuf> {
+    scan_dir(dir, config)
+        .map(|result| result.paths)
+        .unwrap_or_default()
+}
+
+impl Watch<'_> {
+    /// Watches every directory below `dir` that isn't excluded.
+    fn watch_below(&mut self, dir: &Path) {
+        let subdirs = Config {
+            dirs_only: true,
+            threads: self.config.threads,
+            collect_paths: true,
+            exclude: self.config.exclude.clone(),
+            ..Config::default()
+        };
+        for d in scan_paths(dir, &subdirs) {
+            if self.watcher.watch(&d, RecursiveMode::NonRecursive).is_err() {
+                self.unwatched += 1;
+            }
+        }
+    }
+
+    fn entry_for(&self, path: &Path) -> Option<Entry> {
+        let rel = path.strip_prefix(self.root).ok()?;
+        let file_type = std::fs::symlink_metadata(path).ok()?.file_type();
+        let kind = if file_type.is_dir() {
+            EntryKind::Dir
+        } else if file_type.is_symlink() {
+            EntryKind::Symlink
+        } else if file_type.is_file() {
+            EntryKind::File
+        } else {
+            return None;
+        };
+        if is_excluded(rel, file_type.is_dir(), &self.config.exclude) {
+            return None;
+        }
+        Some(Entry {
+            path: path.to_path_buf(),
+            kind,
+            depth: rel.components().count(),
+            metadata: None,
+        })
+    }
+
+    fn added(&mut self, path: PathBuf, kind: Option<&EntryKind>) {
+        if self.matching.insert(path.clone()) {
+            let disp = display_path(&path, self.root, self.f);
+            let painted = paint_match(&disp, &path, kind, self.matcher.as_ref());
+            println!("{} {}", success("+"), hyperlink(&painted, &path));
+        }
+    }
+
+    /// Drops `path` and, if it was a directory, every match below it.
+    fn removed(&mut self, path: &Path) {
+        let gone: Vec<PathBuf> = self
+            .matching
+            .range(path.to_path_buf()..)
+            .take_while(|p| p.starts_with(path))
+            .cloned()
+            .collect();
+        for p in gone {
+            self.matching.remove(&p);
+            println!("{} {}", warning("-"), display_path(&p, self.root, self.f));
+        }
+    }
+
+    /// Re-checks one path against the filesystem. `appeared` marks creates
+    /// and renames, where a new directory may arrive already populated.
+    fn update(&mut self, path: &Path, appeared: bool) {
+        let Some(entry) = self.entry_for(path) else {
+            self.removed(path);
+            return;
+        };
+        if self.matcher.is_match(&entry) {
+            self.added(entry.path.clone(), Some(&entry.kind));
+        } else if self.matching.remove(path) {
+            println!("{} {}", warning("-"), display_path(path, self.root, self.f));
+        }
+        if appeared && matches!(entry.kind, EntryKind::Dir) {
+            if self
+                .watcher
+                .watch(path, RecursiveMode::NonRecursive)
+                .is_err()
+            {
+                self.unwatched += 1;
+            }
+            self.watch_below(path);
+            let scan = Config {
+                collect_paths: true,
+                ..self.config.clone()
+            };
+            for p in scan_paths(path, &scan) {
+                self.added(p, None);
+            }
+        }
+    }
+
+    /// After an overflowed event queue: diff a fresh scan against the set.
+    fn resync(&mut self) {
+        let scan = Config {
+            collect_paths: true,
+            ..self.config.clone()
+        };
+        let now: BTreeSet<PathBuf> = scan_paths(self.root, &scan).into_iter().collect();
+        let gone: Vec<PathBuf> = self.matching.difference(&now).cloned().collect();
+        for p in gone {
+            self.removed(&p);
+        }
+        for p in now {
+            self.added(p, None);
+        }
+    }
+}
+
+/// Follows `root` until the process is interrupted, printing `+ path` when
+/// an entry starts matching and `- path` when it stops. `initial` is the
+/// match set the normal search already printed.
+pub fn run(root: &Path, config: &Config, f: &ParsedFlags, initial: Vec<PathBuf>) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher
+        .watch(root, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Cannot watch {}.", root.display()))?;
+
+    let mut w = Watch {
+        root,
+        config,
+        f,
+        matcher: matcher_for(config),
+        watcher,
+        matching: initial.into_iter().collect(),
+        unwatched: 0,
+    };
+    w.watch_below(root);
+    if w.unwatched > 0 {
+        eprintln!(
+            "{} {} director{} could not be watched (inotify limit?)",
+            warning("⚠"),
+            w.unwatched,
+            if w.unwatched == 1 { "y" } else { "ies" }
+        );
+    }
+    eprintln!("Watching {} — Ctrl-C to stop", root.display());
+
+    for event in rx {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                if f.warn {
+                    eprintln!("  {} {}", warning("watch:"), e);
+                }
+                continue;
+            }
+        };
+        if event.need_rescan() {
+            w.resync();
+            continue;
+        }
+        let appeared = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+        );
+        for path in &event.paths {
+            w.update(path, appeared);
+        }
+    }
+    Ok(())
+}